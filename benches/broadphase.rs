@@ -0,0 +1,104 @@
+//! Times collision detection between many entities, by checking every pair
+//! and by using a `Grid`, and checks that both find the same pairs.
+//!
+//! Run it with `cargo bench`, optionally followed by `-- <n>...` to choose
+//! the numbers of entities.
+
+extern crate arcaders;
+extern crate rand;
+
+use arcaders::phi::data::{Grid, Rectangle};
+use rand::{Rng, SeedableRng, XorShiftRng};
+use std::time::Instant;
+
+/// Frames simulated by each benchmark, whose times are averaged.
+const FRAMES: u32 = 10;
+/// Screen the entities are spread over, for 400 of them. It grows with their
+/// number, so that they are as crowded as in a busy game.
+const SCREEN: (f64, f64) = (800.0, 600.0);
+const CELL_SIZE: f64 = 128.0;
+
+/// Numbers of entities compared when none are given.
+const COUNTS: [usize; 3] = [100, 400, 1600];
+
+/// Compares, for `count` entities split between asteroids and bullets, the
+/// time needed to find which pairs overlap by checking every pair, and by
+/// using a `Grid`. Runs without a window, so that it can run anywhere.
+fn broadphase(count: usize) {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let scale = (count as f64 / 400.0).sqrt().max(1.0);
+    let (w, h) = (SCREEN.0 * scale, SCREEN.1 * scale);
+    let asteroids: Vec<Rectangle> = (0..count / 4)
+        .map(|_| {
+            let side = rng.gen_range(34.0, 96.0);
+            Rectangle {
+                x: rng.gen_range(0.0, w),
+                y: rng.gen_range(0.0, h),
+                w: side,
+                h: side,
+            }
+        })
+        .collect();
+    let bullets: Vec<Rectangle> = (0..count - count / 4)
+        .map(|_| {
+            Rectangle {
+                x: rng.gen_range(0.0, w),
+                y: rng.gen_range(0.0, h),
+                w: 8.0,
+                h: 4.0,
+            }
+        })
+        .collect();
+
+    let start = Instant::now();
+    let mut naive = vec![];
+    for _ in 0..FRAMES {
+        naive.clear();
+        for (j, bullet) in bullets.iter().enumerate() {
+            for (i, asteroid) in asteroids.iter().enumerate() {
+                if asteroid.overlaps(*bullet) {
+                    naive.push((i, j));
+                }
+            }
+        }
+    }
+    let naive_time = start.elapsed();
+
+    // The grid is rebuilt every frame, as the game does
+    let start = Instant::now();
+    let mut pairs = vec![];
+    for _ in 0..FRAMES {
+        let mut grid = Grid::new(CELL_SIZE);
+        for asteroid in &asteroids {
+            grid.insert(*asteroid);
+        }
+        pairs = grid.pairs(&bullets);
+    }
+    let grid_time = start.elapsed();
+
+    let per_frame = |time: ::std::time::Duration| {
+        (time.as_secs() as f64 * 1_000.0 + time.subsec_nanos() as f64 / 1_000_000.0) /
+        FRAMES as f64
+    };
+    println!("{} asteroids, {} bullets, {} overlapping pairs",
+             asteroids.len(),
+             bullets.len(),
+             naive.len());
+    println!("Every pair: {:.3} ms per frame", per_frame(naive_time));
+    println!("Grid:       {:.3} ms per frame", per_frame(grid_time));
+    assert_eq!(pairs, naive, "the grid and the naive check disagree");
+}
+
+fn main() {
+    // `cargo bench` passes its own flags, such as `--bench`
+    let mut counts: Vec<usize> = ::std::env::args()
+        .skip(1)
+        .filter_map(|arg| arg.parse().ok())
+        .collect();
+    if counts.is_empty() {
+        counts = COUNTS.to_vec();
+    }
+    for count in counts {
+        broadphase(count);
+    }
+}
@@ -0,0 +1,7 @@
+extern crate sdl2;
+extern crate rand;
+extern crate toml_edit;
+
+#[macro_use]
+pub mod phi;
+pub mod views;
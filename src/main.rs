@@ -1,17 +1,91 @@
-extern crate sdl2;
+extern crate arcaders;
 extern crate rand;
 
-#[macro_use]
-mod phi;
-mod views;
-use views::shared::Background;
+use arcaders::{phi, views};
+use phi::{Phi, View};
+use std::path::Path;
+
+/// What was asked for on the command line.
+struct Options {
+    /// `--headless [frames]` runs the game without a display, e.g. on CI.
+    headless: Option<u32>,
+    /// `--seed <n>` makes the gameplay randomness reproducible.
+    seed: Option<u64>,
+    /// `--record <file>` saves the player's input to a file, which
+    /// `--replay <file>` plays back, e.g. to reproduce a bug.
+    record: Option<String>,
+    replay: Option<String>,
+    /// `--record-demo` saves the next game as the demo of the main menu.
+    record_demo: bool,
+}
+
+impl Options {
+    fn parse() -> Options {
+        let mut options = Options {
+            headless: None,
+            seed: None,
+            record: None,
+            replay: None,
+            record_demo: false,
+        };
+        let mut args = ::std::env::args().skip(1).peekable();
+        while let Some(arg) = args.next() {
+            match &arg[..] {
+                "--headless" => {
+                    let frames = args.peek().and_then(|f| f.parse().ok());
+                    if frames.is_some() {
+                        args.next();
+                    }
+                    options.headless = Some(frames.unwrap_or(600));
+                }
+                "--seed" => {
+                    options.seed = args.next().and_then(|s| s.parse().ok());
+                    if options.seed.is_none() {
+                        println!("`--seed` expects a number");
+                    }
+                }
+                "--record" => options.record = args.next(),
+                "--replay" => options.replay = args.next(),
+                "--record-demo" => options.record_demo = true,
+                _ => println!("Ignoring unknown argument `{}`", arg),
+            }
+        }
+        options
+    }
+
+    fn first_view(&self, phi: &mut Phi) -> Box<dyn View> {
+        // Shown once, so that a game worth reproducing can be played again
+        // with `--seed`
+        let seed = self.seed.unwrap_or_else(::rand::random);
+        println!("Seed: {}", seed);
+        phi.reseed(seed);
+        if let Some(ref path) = self.replay {
+            if let Err(e) = phi.start_replay(Path::new(path)) {
+                println!("Could not replay {}", e);
+            }
+        }
+        if let Some(ref path) = self.record {
+            if let Err(e) = phi.start_recording(Path::new(path)) {
+                println!("Could not record to {}: {}", path, e);
+            }
+        }
+        phi.record_demo = self.record_demo;
+        Box::new(views::main_menu::MainMenuView::new(phi))
+    }
+}
 
 
 fn main() {
+    let options = Options::parse();
+    if let Some(frames) = options.headless {
+        ::phi::spawn_headless(800, 600, frames, |phi| options.first_view(phi));
+        return;
+    }
+
     // Initialize SDL2
-    ::phi::spawn("ArcadeRS Shooter", |phi| 
+    ::phi::spawn("ArcadeRS Shooter", |phi|
                //  Box::new(views::game::ShipView::new(phi))
-                 Box::new(views::main_menu::MainMenuView::new(phi)));
+                 options.first_view(phi));
 
 
 }
@@ -1,4 +1,6 @@
 use sdl2::rect::Rect as SdlRect;
+use std::collections::HashMap;
+use std::rc::Rc;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Rectangle {
@@ -31,10 +33,75 @@ impl Rectangle {
         ymax >= self.y && ymax <= self.y + self.h
     }
 
+    /// Whether the point `(x, y)` lies inside the rectangle.
+    pub fn contains_point(&self, point: (f64, f64)) -> bool {
+        point.0 >= self.x && point.0 < self.x + self.w && point.1 >= self.y &&
+        point.1 < self.y + self.h
+    }
+
     pub fn overlaps(&self, other: Rectangle) -> bool {
         self.x < other.x + other.w && self.x + self.w > other.x && self.y < other.y + other.h &&
         self.y + self.h > other.y
     }
+    /// Where the segment from `from` to `from + delta` first gets inside the
+    /// rectangle, as a fraction of `delta` between 0 and 1, or `None` if it
+    /// misses it.
+    pub fn ray_hit(&self, from: (f64, f64), delta: (f64, f64)) -> Option<f64> {
+        if self.contains_point(from) {
+            return Some(0.0);
+        }
+        // The fractions of `delta` at which the segment enters and leaves the
+        // slab of each axis. It is inside the rectangle once inside both.
+        let slab = |from: f64, delta: f64, min: f64, max: f64| {
+            if delta == 0.0 {
+                if from >= min && from < max {
+                    Some((f64::NEG_INFINITY, f64::INFINITY))
+                } else {
+                    None
+                }
+            } else {
+                let (a, b) = ((min - from) / delta, (max - from) / delta);
+                Some((a.min(b), a.max(b)))
+            }
+        };
+        let (x_in, x_out) = slab(from.0, delta.0, self.x, self.x + self.w)?;
+        let (y_in, y_out) = slab(from.1, delta.1, self.y, self.y + self.h)?;
+        let (enter, exit) = (x_in.max(y_in), x_out.min(y_out));
+        if enter < exit && (0.0..=1.0).contains(&enter) {
+            Some(enter)
+        } else {
+            None
+        }
+    }
+
+    /// Where `self`, moving by `delta`, first touches `other`, as a fraction
+    /// of `delta` between 0 and 1, or `None` if it never does. Unlike checking
+    /// where `self` ends up, fast objects cannot go through thin ones.
+    pub fn sweep(&self, delta: (f64, f64), other: Rectangle) -> Option<f64> {
+        // Moving `self` against `other` is the same as moving its center
+        // against `other` grown by the size of `self`
+        let grown = Rectangle {
+            x: other.x - self.w / 2.0,
+            y: other.y - self.h / 2.0,
+            w: other.w + self.w,
+            h: other.h + self.h,
+        };
+        grown.ray_hit(self.center(), delta)
+    }
+
+    /// The rectangle covered by `self` while it moved by `motion` to where
+    /// it is now.
+    pub fn swept(self, motion: (f64, f64)) -> Rectangle {
+        let (x0, y0) = (self.x - motion.0, self.y - motion.1);
+        let (x, y) = (x0.min(self.x), y0.min(self.y));
+        Rectangle {
+            x: x,
+            y: y,
+            w: x0.max(self.x) + self.w - x,
+            h: y0.max(self.y) + self.h - y,
+        }
+    }
+
     pub fn move_inside(self, parent: Rectangle) -> Option<Rectangle> {
         // It must be smaller than the parent rectangle to fit in it.
         if self.w > parent.w || self.h > parent.h {
@@ -94,7 +161,505 @@ pub struct MaybeAlive<T> {
 impl<T> MaybeAlive<T> {
     /// Checks whether the value is still alive. If this is the case, then
     /// return `Some(value)`; otherwise, return `None`.
-    pub fn as_option(self) -> Option<T> {
+    pub fn into_option(self) -> Option<T> {
         if self.alive { Some(self.value) } else { None }
     }
 }
+
+/// Which pixels of an image are solid enough to collide with.
+#[derive(Clone, Debug)]
+pub struct Mask {
+    w: usize,
+    h: usize,
+    /// Row by row, from the top-left corner.
+    solid: Vec<bool>,
+}
+
+impl Mask {
+    /// Panics if `solid` does not hold exactly `w * h` pixels.
+    pub fn new(w: usize, h: usize, solid: Vec<bool>) -> Mask {
+        assert_eq!(solid.len(), w * h);
+        Mask {
+            w: w,
+            h: h,
+            solid: solid,
+        }
+    }
+
+    /// Pixels outside of the mask are never solid.
+    pub fn is_solid(&self, x: usize, y: usize) -> bool {
+        x < self.w && y < self.h && self.solid[y * self.w + x]
+    }
+}
+
+/// The outline of an object, as far as collisions are concerned.
+#[derive(Clone, Debug)]
+pub enum Shape {
+    Rect(Rectangle),
+    Circle { center: (f64, f64), radius: f64 },
+    /// A convex polygon, whose points go around it in either direction.
+    /// Build it with `Shape::polygon`, which checks that it has points.
+    Polygon(Vec<(f64, f64)>),
+    /// A mask stretched over a rectangle.
+    Mask(Rc<Mask>, Rectangle),
+}
+
+impl Shape {
+    /// A convex polygon. Panics unless it has at least three points.
+    pub fn polygon(points: Vec<(f64, f64)>) -> Shape {
+        assert!(points.len() >= 3, "a polygon needs at least three points");
+        Shape::Polygon(points)
+    }
+
+    /// The smallest rectangle holding the whole shape.
+    pub fn bounds(&self) -> Rectangle {
+        match *self {
+            Shape::Rect(rect) | Shape::Mask(_, rect) => rect,
+            Shape::Circle { center, radius } => {
+                Rectangle::with_size(2.0 * radius, 2.0 * radius).center_at(center)
+            }
+            Shape::Polygon(ref points) => {
+                let (mut min, mut max) = (points[0], points[0]);
+                for &(x, y) in points {
+                    min = (min.0.min(x), min.1.min(y));
+                    max = (max.0.max(x), max.1.max(y));
+                }
+                Rectangle {
+                    x: min.0,
+                    y: min.1,
+                    w: max.0 - min.0,
+                    h: max.1 - min.1,
+                }
+            }
+        }
+    }
+
+    pub fn overlaps(&self, other: &Shape) -> bool {
+        if !self.bounds().overlaps(other.bounds()) {
+            return false;
+        }
+        match (self, other) {
+            (&Shape::Mask(ref mask, rect), _) => mask_overlaps(mask, rect, other),
+            (_, &Shape::Mask(ref mask, rect)) => mask_overlaps(mask, rect, self),
+            // Overlapping bounds are all it takes
+            (&Shape::Rect(_), &Shape::Rect(_)) => true,
+            (&Shape::Circle { center: a, radius: ra },
+             &Shape::Circle { center: b, radius: rb }) => {
+                let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+                dx * dx + dy * dy < (ra + rb) * (ra + rb)
+            }
+            (&Shape::Circle { center, radius }, &Shape::Rect(rect)) |
+            (&Shape::Rect(rect), &Shape::Circle { center, radius }) => {
+                // The point of the rectangle closest to the center of the circle
+                let x = center.0.max(rect.x).min(rect.x + rect.w);
+                let y = center.1.max(rect.y).min(rect.y + rect.h);
+                let (dx, dy) = (center.0 - x, center.1 - y);
+                dx * dx + dy * dy < radius * radius
+            }
+            (&Shape::Circle { center, radius }, &Shape::Polygon(ref points)) |
+            (&Shape::Polygon(ref points), &Shape::Circle { center, radius }) => {
+                circle_polygon_overlap(center, radius, points)
+            }
+            (Shape::Polygon(a), Shape::Polygon(b)) => polygons_overlap(a, b),
+            (&Shape::Polygon(ref points), &Shape::Rect(rect)) |
+            (&Shape::Rect(rect), &Shape::Polygon(ref points)) => {
+                polygons_overlap(points, &rect_points(rect))
+            }
+        }
+    }
+
+    /// Whether `rect`, which moved by `motion` to where it is now, touched
+    /// the shape along the way.
+    pub fn hit_by(&self, rect: Rectangle, motion: (f64, f64)) -> bool {
+        let start = Rectangle {
+            x: rect.x - motion.0,
+            y: rect.y - motion.1,
+            ..rect
+        };
+        let enter = match start.sweep(motion, self.bounds()) {
+            Some(enter) => enter,
+            None => return false,
+        };
+        if let Shape::Rect(_) = *self {
+            return true;
+        }
+        // Past the bounds, follow the motion in steps smaller than `rect`
+        let distance = (motion.0 * motion.0 + motion.1 * motion.1).sqrt();
+        let step = rect.w.min(rect.h).max(1.0);
+        let steps = ((1.0 - enter) * distance / step).ceil().max(1.0) as u32;
+        (0..steps + 1).any(|i| {
+            let t = enter + (1.0 - enter) * i as f64 / steps as f64;
+            let moved = Rectangle {
+                x: start.x + motion.0 * t,
+                y: start.y + motion.1 * t,
+                ..rect
+            };
+            Shape::Rect(moved).overlaps(self)
+        })
+    }
+}
+
+fn rect_points(rect: Rectangle) -> [(f64, f64); 4] {
+    [(rect.x, rect.y),
+     (rect.x + rect.w, rect.y),
+     (rect.x + rect.w, rect.y + rect.h),
+     (rect.x, rect.y + rect.h)]
+}
+
+/// The normals of the edges of a polygon, i.e. the axes along which it may be
+/// separated from another shape.
+fn edge_normals(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    (0..points.len())
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            (a.1 - b.1, b.0 - a.0)
+        })
+        .collect()
+}
+
+/// The smallest and largest projections of `points` on `axis`.
+fn project(points: &[(f64, f64)], axis: (f64, f64)) -> (f64, f64) {
+    points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+        let d = p.0 * axis.0 + p.1 * axis.1;
+        (min.min(d), max.max(d))
+    })
+}
+
+/// Separating axis test between two convex polygons.
+fn polygons_overlap(a: &[(f64, f64)], b: &[(f64, f64)]) -> bool {
+    edge_normals(a).into_iter().chain(edge_normals(b)).all(|axis| {
+        let (a_min, a_max) = project(a, axis);
+        let (b_min, b_max) = project(b, axis);
+        a_min < b_max && b_min < a_max
+    })
+}
+
+/// Separating axis test between a circle and a convex polygon, which also
+/// checks the axis going through the vertex closest to the circle.
+fn circle_polygon_overlap(center: (f64, f64), radius: f64, points: &[(f64, f64)]) -> bool {
+    let closest = points.iter()
+        .map(|p| (p.0 - center.0, p.1 - center.1))
+        .fold((f64::INFINITY, 0.0), |closest, d| {
+            let d2 = d.0 * d.0 + d.1 * d.1;
+            if d2 < closest.0 * closest.0 + closest.1 * closest.1 { d } else { closest }
+        });
+    edge_normals(points).into_iter().chain(Some(closest)).all(|axis| {
+        let length = (axis.0 * axis.0 + axis.1 * axis.1).sqrt();
+        if length == 0.0 {
+            return true;
+        }
+        let (min, max) = project(points, axis);
+        let c = (center.0 * axis.0 + center.1 * axis.1) / length;
+        min / length < c + radius && c - radius < max / length
+    })
+}
+
+/// Whether any solid pixel of `mask`, stretched over `rect`, overlaps `other`.
+fn mask_overlaps(mask: &Mask, rect: Rectangle, other: &Shape) -> bool {
+    if rect.w <= 0.0 || rect.h <= 0.0 {
+        return false;
+    }
+    let (px_w, px_h) = (rect.w / mask.w as f64, rect.h / mask.h as f64);
+    // Only the pixels under the bounds of the other shape may overlap it
+    let bounds = other.bounds();
+    let index = |at: f64, origin: f64, px: f64| ((at - origin) / px).max(0.0) as usize;
+    let (x0, y0) = (index(bounds.x, rect.x, px_w), index(bounds.y, rect.y, px_h));
+    let x1 = (index(bounds.x + bounds.w, rect.x, px_w) + 1).min(mask.w);
+    let y1 = (index(bounds.y + bounds.h, rect.y, px_h) + 1).min(mask.h);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let pixel = Rectangle {
+                x: rect.x + x as f64 * px_w,
+                y: rect.y + y as f64 * px_h,
+                w: px_w,
+                h: px_h,
+            };
+            if mask.is_solid(x, y) && Shape::Rect(pixel).overlaps(other) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Broad phase of collision detection: sorts rectangles into the cells of a
+/// uniform grid, so that finding those which overlap a given one only
+/// requires looking at its neighbours, instead of at every rectangle.
+pub struct Grid {
+    cell_size: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    rects: Vec<Rectangle>,
+}
+
+impl Grid {
+    /// Cells should be about as large as the objects they hold: larger cells
+    /// hold more candidates, while smaller ones make every object span more
+    /// cells.
+    pub fn new(cell_size: f64) -> Grid {
+        assert!(cell_size > 0.0);
+        Grid {
+            cell_size: cell_size,
+            cells: HashMap::new(),
+            rects: vec![],
+        }
+    }
+
+    /// Adds a rectangle, and returns its index, which counts up from 0.
+    pub fn insert(&mut self, rect: Rectangle) -> usize {
+        let index = self.rects.len();
+        self.rects.push(rect);
+        for cell in self.cells_under(rect) {
+            self.cells.entry(cell).or_default().push(index);
+        }
+        index
+    }
+
+    /// The indices of the rectangles which overlap `rect`, in increasing
+    /// order.
+    pub fn query(&self, rect: Rectangle) -> Vec<usize> {
+        let mut found = vec![];
+        for cell in self.cells_under(rect) {
+            if let Some(indices) = self.cells.get(&cell) {
+                found.extend(indices.iter().filter(|&&i| self.rects[i].overlaps(rect)));
+            }
+        }
+        // Rectangles spanning several cells are found once per cell
+        found.sort();
+        found.dedup();
+        found
+    }
+
+    /// Every pair `(i, j)` such that the `i`th rectangle of the grid overlaps
+    /// `others[j]`, sorted by `j`, then by `i`.
+    pub fn pairs(&self, others: &[Rectangle]) -> Vec<(usize, usize)> {
+        let mut pairs = vec![];
+        for (j, &other) in others.iter().enumerate() {
+            pairs.extend(self.query(other).into_iter().map(|i| (i, j)));
+        }
+        pairs
+    }
+
+    fn cells_under(&self, rect: Rectangle) -> Vec<(i64, i64)> {
+        let cell = |at: f64| (at / self.cell_size).floor() as i64;
+        let (x0, x1) = (cell(rect.x), cell(rect.x + rect.w));
+        let (y0, y1) = (cell(rect.y), cell(rect.y + rect.h));
+        let mut cells = Vec::with_capacity(((x1 - x0 + 1) * (y1 - y0 + 1)) as usize);
+        for y in y0..y1 + 1 {
+            for x in x0..x1 + 1 {
+                cells.push((x, y));
+            }
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64, w: f64, h: f64) -> Rectangle {
+        Rectangle {
+            x: x,
+            y: y,
+            w: w,
+            h: h,
+        }
+    }
+
+    #[test]
+    fn touching_rectangles_do_not_overlap() {
+        let a = rect(0.0, 0.0, 10.0, 10.0);
+        assert!(!a.overlaps(rect(10.0, 0.0, 10.0, 10.0)));
+        assert!(!a.overlaps(rect(0.0, -10.0, 10.0, 10.0)));
+        assert!(a.overlaps(rect(9.5, 9.5, 10.0, 10.0)));
+    }
+
+    #[test]
+    fn rays_enter_where_they_cross_an_edge() {
+        let target = rect(10.0, 0.0, 10.0, 10.0);
+        assert_eq!(target.ray_hit((0.0, 5.0), (20.0, 0.0)), Some(0.5));
+        // Too short, going the wrong way, or passing by
+        assert_eq!(target.ray_hit((0.0, 5.0), (5.0, 0.0)), None);
+        assert_eq!(target.ray_hit((0.0, 5.0), (-20.0, 0.0)), None);
+        assert_eq!(target.ray_hit((0.0, 20.0), (20.0, 0.0)), None);
+        // Sliding along the bottom edge only touches it
+        assert_eq!(target.ray_hit((0.0, 10.0), (40.0, 0.0)), None);
+    }
+
+    #[test]
+    fn rays_starting_inside_hit_at_once() {
+        let target = rect(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(target.ray_hit((5.0, 5.0), (100.0, 0.0)), Some(0.0));
+        assert_eq!(target.ray_hit((5.0, 5.0), (0.0, 0.0)), Some(0.0));
+    }
+
+    #[test]
+    fn rays_along_a_single_axis() {
+        let target = rect(0.0, 0.0, 10.0, 10.0);
+        assert_eq!(target.ray_hit((15.0, 5.0), (0.0, 0.0)), None);
+        assert_eq!(target.ray_hit((-10.0, 5.0), (0.0, 20.0)), None);
+        assert_eq!(target.ray_hit((5.0, -10.0), (0.0, 20.0)), Some(0.5));
+    }
+
+    #[test]
+    fn fast_rectangles_cannot_tunnel_through_thin_ones() {
+        let wall = rect(100.0, 0.0, 2.0, 20.0);
+        // At 60 steps per second, this bullet moves 1000 pixels per step. It
+        // overlaps the wall neither before nor after its motion.
+        let before = rect(0.0, 8.0, 8.0, 4.0);
+        let after = rect(1000.0, 8.0, 8.0, 4.0);
+        assert!(!before.overlaps(wall) && !after.overlaps(wall));
+
+        let enter = before.sweep((1000.0, 0.0), wall).unwrap();
+        assert!((enter - 0.092).abs() < 1e-9);
+        assert!(Shape::Rect(wall).hit_by(after, (1000.0, 0.0)));
+        assert!(!Shape::Rect(wall).hit_by(rect(1000.0, 30.0, 8.0, 4.0), (1000.0, 0.0)));
+    }
+
+    #[test]
+    fn swept_rectangles_cover_the_whole_motion() {
+        assert_eq!(rect(10.0, 10.0, 4.0, 2.0).swept((-6.0, 3.0)), rect(10.0, 7.0, 10.0, 5.0));
+        assert_eq!(rect(1.0, 2.0, 3.0, 4.0).swept((0.0, 0.0)), rect(1.0, 2.0, 3.0, 4.0));
+    }
+
+    #[test]
+    fn touching_circles_do_not_overlap() {
+        let circle = Shape::Circle {
+            center: (0.0, 0.0),
+            radius: 5.0,
+        };
+        assert!(!circle.overlaps(&Shape::Circle {
+                                     center: (10.0, 0.0),
+                                     radius: 5.0,
+                                 }));
+        assert!(circle.overlaps(&Shape::Circle {
+                                    center: (9.0, 0.0),
+                                    radius: 5.0,
+                                }));
+        assert!(!circle.overlaps(&Shape::Rect(rect(5.0, -1.0, 2.0, 2.0))));
+        assert!(circle.overlaps(&Shape::Rect(rect(4.0, -1.0, 2.0, 2.0))));
+        // Within the bounds of the circle, but past its edge
+        assert!(!circle.overlaps(&Shape::Rect(rect(4.0, 4.0, 2.0, 2.0))));
+    }
+
+    #[test]
+    fn circles_are_separated_from_polygon_corners() {
+        let square = Shape::polygon(vec![(4.0, 4.0), (10.0, 4.0), (10.0, 10.0), (4.0, 10.0)]);
+        // No edge of the square separates them, only its closest corner does
+        assert!(!square.overlaps(&Shape::Circle {
+                                     center: (0.0, 0.0),
+                                     radius: 5.0,
+                                 }));
+        assert!(square.overlaps(&Shape::Circle {
+                                    center: (1.0, 1.0),
+                                    radius: 5.0,
+                                }));
+    }
+
+    #[test]
+    fn polygons_are_separated_along_their_edges() {
+        let triangle = Shape::polygon(vec![(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)]);
+        // Within the bounds of the triangle, but past its long side
+        assert!(!triangle.overlaps(&Shape::Rect(rect(6.0, 6.0, 3.0, 3.0))));
+        assert!(triangle.overlaps(&Shape::Rect(rect(4.0, 4.0, 3.0, 3.0))));
+        // Sharing a side is only touching
+        let other = Shape::polygon(vec![(10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]);
+        assert!(!triangle.overlaps(&other));
+    }
+
+    #[test]
+    #[should_panic]
+    fn polygons_need_points() {
+        Shape::polygon(vec![]);
+    }
+
+    #[test]
+    fn masks_only_collide_with_their_solid_pixels() {
+        // A single solid pixel in the bottom-right corner, stretched 10 times
+        let mask = Rc::new(Mask::new(2, 2, vec![false, false, false, true]));
+        let shape = Shape::Mask(mask, rect(0.0, 0.0, 20.0, 20.0));
+        assert!(!shape.overlaps(&Shape::Rect(rect(2.0, 2.0, 5.0, 5.0))));
+        assert!(shape.overlaps(&Shape::Rect(rect(15.0, 15.0, 1.0, 1.0))));
+        // Touching the solid pixel is not enough
+        assert!(!shape.overlaps(&Shape::Rect(rect(5.0, 10.0, 5.0, 5.0))));
+        assert!(shape.overlaps(&Shape::Circle {
+                                   center: (20.0, 20.0),
+                                   radius: 2.0,
+                               }));
+    }
+
+    #[test]
+    fn fast_bullets_hit_shapes_along_their_motion() {
+        let circle = Shape::Circle {
+            center: (100.0, 10.0),
+            radius: 5.0,
+        };
+        assert!(circle.hit_by(rect(1200.0, 8.0, 8.0, 4.0), (1200.0, 0.0)));
+        // Going through the corner of the bounds of the circle, but not
+        // through the circle itself
+        assert!(!circle.hit_by(rect(119.75, 20.25, 1.0, 1.0), (40.0, 40.0)));
+    }
+
+    #[test]
+    fn motionless_bullets_hit_what_they_overlap() {
+        let circle = Shape::Circle {
+            center: (100.0, 10.0),
+            radius: 5.0,
+        };
+        assert!(circle.hit_by(rect(98.0, 8.0, 4.0, 4.0), (0.0, 0.0)));
+        assert!(!circle.hit_by(rect(110.0, 8.0, 4.0, 4.0), (0.0, 0.0)));
+        // Starting inside the circle, and leaving it
+        assert!(circle.hit_by(rect(198.0, 8.0, 4.0, 4.0), (100.0, 0.0)));
+    }
+
+    #[test]
+    fn grids_find_rectangles_across_the_origin() {
+        let mut grid = Grid::new(10.0);
+        let a = grid.insert(rect(-15.0, -15.0, 4.0, 4.0));
+        let b = grid.insert(rect(-3.0, -3.0, 6.0, 6.0));
+        let c = grid.insert(rect(25.0, 25.0, 4.0, 4.0));
+        assert_eq!((a, b, c), (0, 1, 2));
+
+        assert_eq!(grid.query(rect(-14.0, -14.0, 1.0, 1.0)), vec![a]);
+        assert_eq!(grid.query(rect(-1.0, 1.0, 1.0, 1.0)), vec![b]);
+        // In a cell holding `a`, but not touching it
+        assert_eq!(grid.query(rect(-19.0, -19.0, 2.0, 2.0)), Vec::<usize>::new());
+        // Touching is not overlapping, even across cells
+        assert_eq!(grid.query(rect(3.0, -3.0, 5.0, 5.0)), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn grids_find_rectangles_spanning_several_cells_once() {
+        let mut grid = Grid::new(10.0);
+        let wide = grid.insert(rect(-25.0, 5.0, 60.0, 30.0));
+        let small = grid.insert(rect(31.0, 6.0, 2.0, 2.0));
+        assert_eq!(grid.query(rect(-30.0, 0.0, 100.0, 100.0)), vec![wide, small]);
+        assert_eq!(grid.query(rect(30.0, 30.0, 2.0, 2.0)), vec![wide]);
+        assert_eq!(grid.query(rect(-24.0, 34.0, 1.0, 1.0)), vec![wide]);
+    }
+
+    #[test]
+    fn grid_pairs_match_every_pair() {
+        let mut grid = Grid::new(16.0);
+        let rects = [rect(-40.0, -8.0, 50.0, 10.0),
+                     rect(0.0, 0.0, 10.0, 10.0),
+                     rect(30.0, -30.0, 3.0, 70.0)];
+        for &r in &rects {
+            grid.insert(r);
+        }
+        let others = [rect(5.0, 1.0, 40.0, 2.0),
+                      rect(-35.0, -5.0, 2.0, 2.0),
+                      rect(100.0, 100.0, 5.0, 5.0)];
+
+        let mut naive = vec![];
+        for (j, other) in others.iter().enumerate() {
+            for (i, r) in rects.iter().enumerate() {
+                if r.overlaps(*other) {
+                    naive.push((i, j));
+                }
+            }
+        }
+        assert_eq!(naive, vec![(0, 0), (1, 0), (2, 0), (0, 1)]);
+        assert_eq!(grid.pairs(&others), naive);
+    }
+}
@@ -2,14 +2,67 @@ macro_rules! struct_events {
     (
         keyboard: { $( $k_alias:ident : $k_sdl:ident ),* },
 
+        controller: { $( $c_alias:ident : $c_sdl:ident ),* },
+
         else: { $( $e_alias:ident : $e_sdl:pat ),* }
     )
     => {
-        use sdl2::EventPump;
+        use sdl2::{EventPump, GameControllerSubsystem};
+        use sdl2::controller::{Axis, Button, GameController};
+        use sdl2::event::Event;
+        use sdl2::keyboard::Keycode;
+        use sdl2::mouse::MouseButton;
+        use std::collections::HashSet;
+        use phi::replay::StepInput;
+
+        /// Mouse events which SDL synthesizes from touch events carry this id.
+        /// They are ignored, as touch events are handled on their own.
+        const TOUCH_MOUSE_ID: u32 = u32::MAX;
+
+
+        /// A physical input which may be bound to a logical key.
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum Input {
+            Key(Keycode),
+            Button(Button),
+        }
+
+        impl Input {
+            /// Human-readable name, which `from_name` understands. Controller
+            /// buttons are prefixed with `pad:`, e.g. `pad:dpup`.
+            pub fn name(&self) -> String {
+                match *self {
+                    Input::Key(keycode) => keycode.name(),
+                    Input::Button(button) => format!("pad:{}", button.string()),
+                }
+            }
+
+            pub fn from_name(name: &str) -> Option<Input> {
+                if let Some(button) = name.strip_prefix("pad:") {
+                    Button::from_string(button).map(Input::Button)
+                } else {
+                    Keycode::from_name(name).map(Input::Key)
+                }
+            }
+        }
 
 
         pub struct ImmediateEvents {
             resize: Option<(u32, u32)>,
+            /// The last physical input pressed during this step, whether or
+            /// not it is bound to anything. Used to rebind keys.
+            pub pressed: Option<Input>,
+            /// Whether the mouse, or a finger, moved.
+            pub mouse_moved: bool,
+            /// Where the left mouse button was pressed, or the screen touched,
+            /// in pixels.
+            pub click: Option<(f64, f64)>,
+            /// How far the mouse wheel was scrolled, horizontally and
+            /// vertically. Positive values point right and away from the user.
+            pub wheel: (i32, i32),
+            /// Whether the player pressed anything while a recording was
+            /// being played back.
+            pub interrupted: bool,
             $( pub $k_alias : Option<bool> , )*
             $( pub $e_alias : bool ),*
         }
@@ -18,16 +71,98 @@ macro_rules! struct_events {
             pub fn new() -> ImmediateEvents {
                 ImmediateEvents {
                     resize: None,
+                    pressed: None,
+                    mouse_moved: false,
+                    click: None,
+                    wheel: (0, 0),
+                    interrupted: false,
                     $( $k_alias: None , )*
                     $( $e_alias: false ),*
                 }
             }
         }
 
+        impl Default for ImmediateEvents {
+            fn default() -> ImmediateEvents {
+                ImmediateEvents::new()
+            }
+        }
+
+        /// The physical inputs which trigger every logical key. A logical key
+        /// is pressed as long as any of its physical inputs is held down.
+        #[derive(Clone)]
+        pub struct Bindings {
+            $( pub $k_alias: Vec<Input>, )*
+            /// Fraction of the analog stick's range, from its center, which
+            /// is ignored to compensate for worn or imprecise sticks.
+            pub dead_zone: f64,
+        }
+
+        impl Default for Bindings {
+            /// The bindings given to `struct_events!`.
+            fn default() -> Bindings {
+                let mut bindings = Bindings {
+                    $( $k_alias: vec![Input::Key(Keycode::$k_sdl)], )*
+                    dead_zone: 0.25,
+                };
+                $( bindings.$c_alias.push(Input::Button(Button::$c_sdl)); )*
+                bindings
+            }
+        }
+
+        impl Bindings {
+            /// The names of the logical keys, in the order they were declared.
+            pub fn names() -> Vec<&'static str> {
+                vec![ $( stringify!($k_alias) ),* ]
+            }
+
+            pub fn get(&self, name: &str) -> Option<&Vec<Input>> {
+                match name {
+                    $( stringify!($k_alias) => Some(&self.$k_alias), )*
+                    _ => None,
+                }
+            }
+
+            pub fn get_mut(&mut self, name: &str) -> Option<&mut Vec<Input>> {
+                match name {
+                    $( stringify!($k_alias) => Some(&mut self.$k_alias), )*
+                    _ => None,
+                }
+            }
+        }
+
 
         pub struct Events {
             pump: EventPump,
+            /// `None` if SDL could not enable game controllers, in which case
+            /// the player makes do with the keyboard and the mouse.
+            controllers: Option<GameControllerSubsystem>,
+            /// The controllers currently plugged in, by instance id.
+            open_controllers: ::std::collections::HashMap<i32, GameController>,
             pub now: ImmediateEvents,
+            pub bindings: Bindings,
+            /// The keys currently held down.
+            held: HashSet<Keycode>,
+            /// The buttons currently held down on every controller, by
+            /// instance id.
+            held_buttons: ::std::collections::HashMap<i32, HashSet<Button>>,
+            /// Raw position of the left analog stick, in [-1, 1].
+            raw_stick: (f64, f64),
+            /// Instance id of the controller whose stick moved last.
+            stick_controller: Option<i32>,
+            /// Position of the left analog stick once the dead zone has been
+            /// taken into account. Its length is at most 1.
+            pub stick: (f64, f64),
+            /// Size of the window, in pixels, to locate touch events.
+            pub window_size: (u32, u32),
+            /// Position of the mouse, or of the last finger to touch the
+            /// screen, in pixels.
+            pub mouse: (f64, f64),
+            pub mouse_left: bool,
+            pub mouse_middle: bool,
+            pub mouse_right: bool,
+            recorder: Option<::phi::replay::Recorder>,
+            replay: Option<::phi::replay::Replay>,
 
             // true  => pressed
             // false => not pressed
@@ -35,72 +170,395 @@ macro_rules! struct_events {
         }
 
         impl Events {
-            pub fn new(pump: EventPump) -> Events {
+            pub fn new(pump: EventPump,
+                       controllers: Option<GameControllerSubsystem>,
+                       window_size: (u32, u32))
+                       -> Events {
                 Events {
                     pump: pump,
+                    controllers: controllers,
+                    open_controllers: ::std::collections::HashMap::new(),
                     now: ImmediateEvents::new(),
+                    bindings: Bindings::default(),
+                    held: HashSet::new(),
+                    held_buttons: ::std::collections::HashMap::new(),
+                    raw_stick: (0.0, 0.0),
+                    stick_controller: None,
+                    stick: (0.0, 0.0),
+                    window_size: window_size,
+                    mouse: (0.0, 0.0),
+                    mouse_left: false,
+                    mouse_middle: false,
+                    mouse_right: false,
+                    recorder: None,
+                    replay: None,
 
                     // By default, initialize every key with _not pressed_
                     $( $k_alias: false ),*
                 }
             }
 
-            pub fn pump(&mut self, renderer: &mut ::sdl2::render::Renderer) {
+            /// Processes every event which happened since the last step.
+            ///
+            /// While a recording is played back, the logical input comes from
+            /// the recording instead, and live events only tell whether the
+            /// player interrupted it. While recording, the logical input of
+            /// the step is written out.
+            pub fn pump(&mut self) {
                 self.now = ImmediateEvents::new();
 
-                for event in self.pump.poll_iter() {
-                    use sdl2::event::Event::*;
-                    use sdl2::event::WindowEvent::Resized;
-                    use sdl2::event::WindowEvent::Shown;
-                    use sdl2::keyboard::Keycode::*;
-                  //  use sdl2::video::Window;
-                    match event {
-                        Window { win_event: Resized(_,_),..  } => {
-                            self.now.resize = Some(renderer.output_size().unwrap());
-                        },
-
-                        KeyDown { keycode, .. } => match keycode {
-                            // $( ... ),* containing $k_sdl and $k_alias means:
-                            //   "for every element ($k_alias : $k_sdl) pair,
-                            //    check whether the keycode is Some($k_sdl). If
-                            //    it is, then set the $k_alias fields to true."
-                            $(
-                                Some($k_sdl) => {
-                                    // Prevent multiple presses when keeping a key down
-                                    // Was previously not pressed?
-                                    if !self.$k_alias {
-                                        // Key pressed
-                                        self.now.$k_alias = Some(true);
-                                    }
-
-                                    self.$k_alias = true;
-                                }
-                            ),* // and add a comma after every option
-                            _ => {}
-                        },
-
-                        KeyUp { keycode, .. } => match keycode {
-                            $(
-                                Some($k_sdl) => {
-                                    // Key released
-                                    self.now.$k_alias = Some(false);
-                                    self.$k_alias = false;
-                                }
-                            ),*
-                            _ => {}
-                        },
+                let events: Vec<Event> = self.pump.poll_iter().collect();
+                match self.replay.as_mut().and_then(::phi::replay::Replay::next_step) {
+                    Some(input) => {
+                        for event in events {
+                            self.handle_live(event);
+                        }
+                        self.apply(&input);
+                    }
+                    None => {
+                        if self.replay.is_some() {
+                            self.stop_replay();
+                        }
+                        for event in events {
+                            self.handle(event);
+                        }
+                        self.refresh();
+                    }
+                }
+
+                if self.recorder.is_some() {
+                    // Read the input back from what was recorded, so that
+                    // the game sees exactly what a replay will.
+                    let input = self.snapshot();
+                    self.apply(&input);
+                    let result = self.recorder.as_mut().unwrap().step(&input);
+                    if let Err(e) = result {
+                        println!("Could not record the input: {}", e);
+                        self.recorder = None;
+                    }
+                }
+            }
+
+            /// Writes every following step to `recorder`, replacing any
+            /// ongoing recording.
+            pub fn record(&mut self, recorder: ::phi::replay::Recorder) {
+                self.stop_recording();
+                self.recorder = Some(recorder);
+            }
+
+            pub fn is_recording(&self) -> bool {
+                self.recorder.is_some()
+            }
+
+            pub fn stop_recording(&mut self) {
+                if let Some(recorder) = self.recorder.take() {
+                    if let Err(e) = recorder.finish() {
+                        println!("Could not save the recording: {}", e);
+                    }
+                }
+            }
+
+            /// Feeds every following step from `replay` instead of SDL, until
+            /// it is over or `stop_replay` is called. Whatever was held down
+            /// when the recording started is held down again, without
+            /// counting as a press.
+            pub fn replay(&mut self, replay: ::phi::replay::Replay) {
+                let held = replay.held();
+                self.apply(&held);
+                self.now = ImmediateEvents::new();
+                self.replay = Some(replay);
+            }
+
+            pub fn is_replaying(&self) -> bool {
+                self.replay.is_some()
+            }
+
+            /// Goes back to live input. Whatever the recording held down is
+            /// released.
+            pub fn stop_replay(&mut self) {
+                self.replay = None;
+                self.held.clear();
+                self.held_buttons.clear();
+                self.raw_stick = (0.0, 0.0);
+                self.mouse_left = false;
+                self.mouse_middle = false;
+                self.mouse_right = false;
+            }
+
+            /// Called by the main loop once per frame with how long it took,
+            /// in milliseconds. Returns the duration the loop should use,
+            /// which is the recorded one during a replay.
+            pub fn frame_time(&mut self, ms: u32) -> u32 {
+                let ms = self.replay.as_mut().and_then(::phi::replay::Replay::next_frame).unwrap_or(ms);
+                let result = self.recorder.as_mut().map_or(Ok(()), |r| r.frame(ms));
+                if let Err(e) = result {
+                    println!("Could not record the frame time: {}", e);
+                    self.recorder = None;
+                }
+                ms
+            }
+
+            /// The logical input which is held down, leaving out what only
+            /// happened during the current step, such as clicks.
+            pub fn held(&self) -> StepInput {
+                let mut input = StepInput::default();
+                let keys = [$( self.$k_alias ),*];
+                for (i, &pressed) in keys.iter().enumerate() {
+                    if pressed {
+                        input.keys |= 1 << i;
+                    }
+                }
+
+                for &(pressed, flag) in &[(self.mouse_left, ::phi::replay::MOUSE_LEFT),
+                                          (self.mouse_middle, ::phi::replay::MOUSE_MIDDLE),
+                                          (self.mouse_right, ::phi::replay::MOUSE_RIGHT)] {
+                    if pressed {
+                        input.mouse_flags |= flag;
+                    }
+                }
+                input.set_stick(self.stick);
+                input.set_mouse(self.mouse);
+                input
+            }
+
+            /// The logical input of the current step. Events such as `quit`
+            /// are left out, so that a replay never closes the game.
+            fn snapshot(&self) -> StepInput {
+                let mut input = self.held();
+                if self.now.mouse_moved {
+                    input.mouse_flags |= ::phi::replay::MOUSE_MOVED;
+                }
+                input.set_click(self.now.click);
+                input.wheel = (clamp_wheel(self.now.wheel.0), clamp_wheel(self.now.wheel.1));
+                input
+            }
+
+            /// Sets the logical input of the current step, recording the
+            /// presses and releases of the logical keys as `refresh` does.
+            fn apply(&mut self, input: &StepInput) {
+                {
+                    let mut keys = [$( &mut self.$k_alias ),*];
+                    let mut changes = [$( &mut self.now.$k_alias ),*];
+                    for (i, (key, change)) in keys.iter_mut().zip(changes.iter_mut()).enumerate() {
+                        let pressed = input.keys & (1 << i) != 0;
+                        if pressed != **key {
+                            **change = Some(pressed);
+                            **key = pressed;
+                        }
+                    }
+                }
+
+                self.mouse_left = input.mouse_flags & ::phi::replay::MOUSE_LEFT != 0;
+                self.mouse_middle = input.mouse_flags & ::phi::replay::MOUSE_MIDDLE != 0;
+                self.mouse_right = input.mouse_flags & ::phi::replay::MOUSE_RIGHT != 0;
+                self.now.mouse_moved = input.mouse_flags & ::phi::replay::MOUSE_MOVED != 0;
+                self.now.click = input.click();
+                self.stick = input.stick();
+                self.mouse = input.mouse();
+                self.now.wheel = (input.wheel.0 as i32, input.wheel.1 as i32);
+            }
+
+            /// Processes an event which happened during a replay. Presses
+            /// interrupt the replay rather than reaching the game, but the
+            /// window and the controllers are still taken care of.
+            fn handle_live(&mut self, event: Event) {
+                use sdl2::event::Event::*;
+                match event {
+                    KeyDown { repeat: false, .. } |
+                    ControllerButtonDown { .. } |
+                    MouseButtonDown { .. } |
+                    FingerDown { .. } => {
+                        self.now.interrupted = true;
+                    },
+
+                    Window { .. } |
+                    ControllerDeviceAdded { .. } |
+                    ControllerDeviceRemoved { .. } => {
+                        self.handle(event);
+                    },
+
+                    $(
+                        $e_sdl => {
+                            self.now.$e_alias = true;
+                        }
+                    )*,
+
+                    _ => {}
+                }
+            }
+
+            /// Processes a single event, as if SDL had just reported it. This
+            /// can be used to feed synthetic events; `refresh` must then be
+            /// called for the logical keys to reflect them.
+            pub fn handle(&mut self, event: Event) {
+                use sdl2::event::Event::*;
+                use sdl2::event::WindowEvent::Resized;
+                match event {
+                    Window { win_event: Resized(w, h),..  } => {
+                        self.now.resize = Some((w as u32, h as u32));
+                        self.window_size = (w as u32, h as u32);
+                    },
+
+                    // Prevent multiple presses when keeping a key down
+                    KeyDown { keycode: Some(keycode), repeat: false, .. } => {
+                        self.now.pressed = Some(Input::Key(keycode));
+                        self.held.insert(keycode);
+                    },
+
+                    KeyUp { keycode: Some(keycode), .. } => {
+                        self.held.remove(&keycode);
+                    },
+
+                    MouseMotion { which, x, y, .. } if which != TOUCH_MOUSE_ID => {
+                        self.mouse = (x as f64, y as f64);
+                        self.now.mouse_moved = true;
+                    },
 
-                        $(
-                            $e_sdl => {
-                                self.now.$e_alias = true;
+                    MouseButtonDown { which, mouse_btn, x, y, .. } if which != TOUCH_MOUSE_ID => {
+                        self.mouse = (x as f64, y as f64);
+                        self.set_mouse_button(mouse_btn, true);
+                        if mouse_btn == MouseButton::Left {
+                            self.now.click = Some(self.mouse);
+                        }
+                    },
+
+                    MouseButtonUp { which, mouse_btn, .. } if which != TOUCH_MOUSE_ID => {
+                        self.set_mouse_button(mouse_btn, false);
+                    },
+
+                    MouseWheel { which, x, y, .. } if which != TOUCH_MOUSE_ID => {
+                        self.now.wheel.0 += x;
+                        self.now.wheel.1 += y;
+                    },
+
+                    // Fingers behave like the left mouse button. Their
+                    // position is given as a fraction of the window's size.
+                    FingerDown { x, y, .. } => {
+                        self.mouse = self.touch_position(x, y);
+                        self.mouse_left = true;
+                        self.now.mouse_moved = true;
+                        self.now.click = Some(self.mouse);
+                    },
+
+                    FingerMotion { x, y, .. } => {
+                        self.mouse = self.touch_position(x, y);
+                        self.now.mouse_moved = true;
+                    },
+
+                    FingerUp { x, y, .. } => {
+                        self.mouse = self.touch_position(x, y);
+                        self.mouse_left = false;
+                    },
+
+                    ControllerDeviceAdded { which, .. } => {
+                        if let Some(ref controllers) = self.controllers {
+                            match controllers.open(which as u32) {
+                                Ok(controller) => {
+                                    self.open_controllers.insert(controller.instance_id(), controller);
+                                }
+                                Err(e) => println!("Could not open controller {}: {:?}", which, e),
                             }
-                        )*,
+                        }
+                    },
 
+                    ControllerDeviceRemoved { which, .. } => {
+                        self.open_controllers.remove(&which);
+                        // Release what this controller held, lest a button
+                        // stays stuck. Other controllers are left alone.
+                        self.held_buttons.remove(&which);
+                        if self.stick_controller == Some(which) {
+                            self.stick_controller = None;
+                            self.raw_stick = (0.0, 0.0);
+                        }
+                    },
 
-                        _ => {}
+                    ControllerButtonDown { which, button, .. } => {
+                        self.now.pressed = Some(Input::Button(button));
+                        self.held_buttons.entry(which).or_insert_with(HashSet::new).insert(button);
+                    },
+
+                    ControllerButtonUp { which, button, .. } => {
+                        if let Some(buttons) = self.held_buttons.get_mut(&which) {
+                            buttons.remove(&button);
+                        }
+                    },
+
+                    ControllerAxisMotion { which, axis: Axis::LeftX, value, .. } => {
+                        self.raw_stick.0 = value as f64 / 32_767.0;
+                        self.stick_controller = Some(which);
+                    },
+
+                    ControllerAxisMotion { which, axis: Axis::LeftY, value, .. } => {
+                        self.raw_stick.1 = value as f64 / 32_767.0;
+                        self.stick_controller = Some(which);
+                    },
+
+                    $(
+                        $e_sdl => {
+                            self.now.$e_alias = true;
+                        }
+                    )*,
+
+
+                    _ => {}
+                }
+            }
+
+            fn set_mouse_button(&mut self, button: MouseButton, pressed: bool) {
+                match button {
+                    MouseButton::Left => self.mouse_left = pressed,
+                    MouseButton::Middle => self.mouse_middle = pressed,
+                    MouseButton::Right => self.mouse_right = pressed,
+                    _ => {}
+                }
+            }
+
+            fn touch_position(&self, x: f32, y: f32) -> (f64, f64) {
+                (x as f64 * self.window_size.0 as f64, y as f64 * self.window_size.1 as f64)
+            }
+
+            /// Whether `input` is held down, on any controller.
+            fn is_held(&self, input: &Input) -> bool {
+                match *input {
+                    Input::Key(keycode) => self.held.contains(&keycode),
+                    Input::Button(button) => {
+                        self.held_buttons.values().any(|buttons| buttons.contains(&button))
                     }
                 }
             }
+
+            /// Brings the logical keys and the analog stick up to date with
+            /// the physical inputs.
+            pub fn refresh(&mut self) {
+                // $( ... )* containing $k_alias means: "for every logical
+                // key, check whether any of its physical inputs is held. If
+                // that changed since the last step, then record the press or
+                // the release."
+                $(
+                    let pressed = self.bindings.$k_alias.iter().any(|i| self.is_held(i));
+                    if pressed != self.$k_alias {
+                        self.now.$k_alias = Some(pressed);
+                        self.$k_alias = pressed;
+                    }
+                )*
+
+                // Ignore the dead zone, and rescale what remains of the range
+                // so that the stick still goes from 0 to 1.
+                let (x, y) = self.raw_stick;
+                let length = (x * x + y * y).sqrt();
+                let dead_zone = self.bindings.dead_zone;
+                self.stick = if length <= dead_zone {
+                    (0.0, 0.0)
+                } else {
+                    let scale = ((length - dead_zone) / (1.0 - dead_zone)).min(1.0) / length;
+                    (x * scale, y * scale)
+                };
+            }
+        }
+
+        /// Fits a number of wheel notches into a recorded step.
+        fn clamp_wheel(notches: i32) -> i8 {
+            notches.max(i8::MIN as i32).min(i8::MAX as i32) as i8
         }
     }
 }
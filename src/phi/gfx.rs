@@ -1,8 +1,9 @@
 //define sprite type using Arc
-use std::sync::Arc;
-use phi::data::Rectangle;
-use sdl2::render::{Texture, TextureQuery, Renderer};
-use sdl2::image::LoadTexture;
+use phi::data::{Mask, Rectangle};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::{BlendMode, Texture, Renderer};
+use sdl2::image::{LoadSurface, LoadTexture};
+use sdl2::surface::Surface;
 use std::path::Path;
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -13,20 +14,19 @@ use std::rc::Rc;
 pub struct Sprite {
     tex: Rc<RefCell<Texture>>,
     src: Rectangle,
+    /// The image the texture was loaded from, if any.
+    path: Option<Rc<String>>,
 }
 impl Sprite {
-    pub fn clone(&self) -> Sprite {
-        Sprite {
-            tex: self.tex.clone(),
-            src: self.src.clone(),
-        }
-    }
     pub fn load(renderer: &Renderer, path: &str) -> Option<Sprite> {
-        renderer.load_texture(Path::new(path)).ok().map(Sprite::new)
+        renderer.load_texture(Path::new(path)).ok().map(|texture| {
+            Sprite { path: Some(Rc::new(path.to_string())), ..Sprite::new(texture) }
+        })
     }
     pub fn new(texture: Texture) -> Sprite {
         let tex_query = texture.query();
         Sprite {
+            path: None,
             tex: Rc::new(RefCell::new(texture)),
             src: Rectangle {
                 w: tex_query.width as f64,
@@ -50,6 +50,7 @@ impl Sprite {
             Some(Sprite {
                      tex: self.tex.clone(),
                      src: new_src,
+                     path: self.path.clone(),
                  })
         } else {
             None
@@ -58,11 +59,54 @@ impl Sprite {
     pub fn size(&self) -> (f64, f64) {
         (self.src.w, self.src.h)
     }
+    /// The pixels of the sprite whose alpha is above `threshold`, for
+    /// pixel-perfect collisions. Textures cannot be read back, so the image
+    /// is loaded again: this only works for sprites created by `load`.
+    pub fn mask(&self, threshold: u8) -> Option<Mask> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return None,
+        };
+        let mut image = Surface::from_file(Path::new(&**path)).ok()?;
+        // Copy the alpha channel as is, instead of blending it
+        image.set_blend_mode(BlendMode::None).ok()?;
+        let (w, h) = (self.src.w as u32, self.src.h as u32);
+        let mut region = Surface::new(w, h, PixelFormatEnum::RGBA8888).ok()?;
+        image.blit(self.src.to_sdl(), &mut region, None).ok()?;
+
+        let pitch = region.pitch() as usize;
+        let solid = region.with_lock(|pixels| {
+            let mut solid = Vec::with_capacity((w * h) as usize);
+            for y in 0..h as usize {
+                for x in 0..w as usize {
+                    let i = y * pitch + x * 4;
+                    let pixel = u32::from_ne_bytes([pixels[i], pixels[i + 1], pixels[i + 2],
+                                                    pixels[i + 3]]);
+                    // RGBA8888 keeps the alpha in the lowest byte
+                    solid.push((pixel & 0xff) as u8 > threshold);
+                }
+            }
+            solid
+        });
+        Some(Mask::new(w as usize, h as usize, solid))
+    }
+    /// Same as `render`, but rotates the sprite by `angle` degrees clockwise
+    /// around its center, and mirrors it horizontally if `flip` is set.
+    pub fn render_ex(&self, renderer: &mut Renderer, dest: Rectangle, angle: f64, flip: bool) {
+        renderer.copy_ex(&self.tex.borrow_mut(),
+                     self.src.to_sdl(),
+                     dest.to_sdl(),
+                     angle,
+                     None,
+                     flip,
+                     false)
+            .unwrap();
+    }
 }
 
 impl Renderable for Sprite {
     fn render(&self, renderer: &mut Renderer, dest: Rectangle) {
-        renderer.copy(&mut self.tex.borrow_mut(), self.src.to_sdl(), dest.to_sdl());
+        let _ = renderer.copy(&self.tex.borrow_mut(), self.src.to_sdl(), dest.to_sdl());
     }
 }
 #[derive(Clone)]
@@ -115,7 +159,7 @@ impl AnimatedSprite {
         // Read the asteroid's image from the filesystem and construct an
         // animated sprite out of it.
 
-        let spritesheet = Sprite::load(&mut phi.renderer, descr.image_path).unwrap();
+        let spritesheet = Sprite::load(&phi.renderer, descr.image_path).unwrap();
         let mut frames = Vec::with_capacity(descr.total_frames);
 
         for yth in 0..descr.frames_high {
@@ -137,13 +181,22 @@ impl AnimatedSprite {
         frames
     }
 }
+impl AnimatedSprite {
+    fn current(&self) -> &Sprite {
+        let current_frame = (self.current_time / self.frame_delay) as usize % self.frames();
+        &self.sprites[current_frame]
+    }
+    /// Same as `render`, but rotates the current frame by `angle` degrees
+    /// clockwise around its center, and mirrors it horizontally if `flip` is
+    /// set.
+    pub fn render_ex(&self, renderer: &mut Renderer, dest: Rectangle, angle: f64, flip: bool) {
+        self.current().render_ex(renderer, dest, angle, flip);
+    }
+}
 impl Renderable for AnimatedSprite {
     /// Renders the current frame of the sprite.
     fn render(&self, renderer: &mut Renderer, dest: Rectangle) {
-        let current_frame = (self.current_time / self.frame_delay) as usize % self.frames();
-
-        let sprite = &self.sprites[current_frame];
-        sprite.render(renderer, dest);
+        self.current().render(renderer, dest);
     }
 }
 pub trait Renderable {
@@ -2,11 +2,18 @@
 mod events;
 pub mod data;
 pub mod gfx;
+pub mod replay;
 use self::gfx::Sprite;
+use self::replay::{Recorder, Replay};
+use rand::{Rng, SeedableRng, XorShiftRng};
 use sdl2::render::Renderer;
-use sdl2::pixels::Color;
+use sdl2::surface::Surface;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::{Sdl, TimerSubsystem};
 use std::collections::HashMap;
-use std::path::Path;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
 use sdl2::ttf::Sdl2TtfContext;
 struct_events! {
     keyboard: {
@@ -21,14 +28,173 @@ struct_events! {
         key_2:Num2,
         key_3:Num3
     },
+    controller: {
+        key_escape: Back,
+        key_up: DPadUp,
+        key_down: DPadDown,
+        key_left: DPadLeft,
+        key_right: DPadRight,
+        key_space: A,
+        key_enter: Start,
+        key_1: X,
+        key_2: Y,
+        key_3: B
+    },
     else: {
         quit: Quit { .. }
     }
 }
+
+const BINDINGS_FILE: &str = "controls.cfg";
+/// Any larger dead zone would leave the analog stick almost useless.
+const MAX_DEAD_ZONE: f64 = 0.9;
+/// First line of the bindings file. Bump the version whenever the format
+/// changes, so that older files are ignored instead of misread.
+const BINDINGS_HEADER: &str = "# arcaders controls v3";
+
+/// The bindings are stored in the user's data directory as a plain text file,
+/// which starts with `BINDINGS_HEADER` and lists the physical inputs of every
+/// logical key, e.g. `key_up = "Up", "W", "pad:dpup"`, on its own line. Their
+/// names are quoted, as some keys are called `,` or `"`, and backslashes
+/// escape the quotes and backslashes within. The analog stick's dead zone is
+/// stored as `dead_zone = 0.25`.
+impl Bindings {
+    /// Reads the bindings saved by the player. Logical keys which are missing
+    /// from the file, or could not be read, keep their default binding.
+    pub fn load() -> Bindings {
+        let mut bindings = Bindings::default();
+        let path = match user_file(BINDINGS_FILE) {
+            Some(path) => path,
+            None => return bindings,
+        };
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(_) => return bindings,
+        };
+
+        let mut lines = BufReader::new(file).lines();
+        match lines.next() {
+            Some(Ok(ref header)) if header == BINDINGS_HEADER => {}
+            _ => {
+                println!("Ignoring {}: unknown controls format", path.display());
+                return bindings;
+            }
+        }
+        for (i, line) in lines.enumerate() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            if let Err(e) = bindings.parse_line(&line) {
+                println!("{}:{}: {}", path.display(), i + 2, e);
+            }
+        }
+
+        bindings
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+
+        let mut fields = line.splitn(2, '=');
+        let name = fields.next().unwrap().trim();
+        let value = fields.next().ok_or(format!("expected `{} = <value>`", name))?.trim();
+        if name == "dead_zone" {
+            let dead_zone: f64 = value.parse().map_err(|_| format!("invalid dead zone `{}`", value))?;
+            if !(0.0..=MAX_DEAD_ZONE).contains(&dead_zone) {
+                return Err(format!("dead zone must be between 0 and {}", MAX_DEAD_ZONE));
+            }
+            self.dead_zone = dead_zone;
+            return Ok(());
+        }
+
+        let inputs = parse_inputs(value)?;
+        *self.get_mut(name).ok_or(format!("unknown action `{}`", name))? = inputs;
+        Ok(())
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = user_file(BINDINGS_FILE)
+            .ok_or(io::Error::new(io::ErrorKind::NotFound, "no user data directory"))?;
+        self.write(&mut File::create(path)?)
+    }
+
+    fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "{}", BINDINGS_HEADER)?;
+        for name in Bindings::names() {
+            let inputs = self.get(name).unwrap().iter().map(|input| quote(&input.name())).collect::<Vec<_>>();
+            writeln!(out, "{} = {}", name, inputs.join(", "))?;
+        }
+        writeln!(out, "dead_zone = {}", self.dead_zone)
+    }
+
+    /// Lists the physical inputs bound to the logical key called `name`.
+    pub fn describe(&self, name: &str) -> String {
+        self.get(name)
+            .map(|inputs| inputs.iter().map(Input::name).collect::<Vec<_>>().join(", "))
+            .unwrap_or_default()
+    }
+}
+
+/// Reads a comma-separated list of quoted input names, as written by `quote`.
+/// The list is empty if the player unbound every input of the key.
+fn parse_inputs(value: &str) -> Result<Vec<Input>, String> {
+    let mut inputs = vec![];
+    if value.trim().is_empty() {
+        return Ok(inputs);
+    }
+    let mut chars = value.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        match chars.next() {
+            Some('"') => {}
+            Some(c) => return Err(format!("expected a quoted input, found `{}`", c)),
+            None => return Err("expected a quoted input".to_string()),
+        }
+
+        let mut name = String::new();
+        loop {
+            match chars.next() {
+                Some('\\') => name.push(chars.next().ok_or("unterminated input name")?),
+                Some('"') => break,
+                Some(c) => name.push(c),
+                None => return Err("unterminated input name".to_string()),
+            }
+        }
+        inputs.push(Input::from_name(&name).ok_or(format!("unknown input `{}`", name))?);
+
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        match chars.next() {
+            Some(',') => {}
+            Some(c) => return Err(format!("expected `,`, found `{}`", c)),
+            None => return Ok(inputs),
+        }
+    }
+}
+
+/// Puts `name` between quotes, escaping the quotes and backslashes within.
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
 pub struct Phi<'window> {
     pub events: Events,
     pub renderer: Renderer<'window>,
     pub ttf_context: &'window Sdl2TtfContext,
+    /// Source of every random decision taken by the game, so that a run
+    /// only depends on its seed and on the player's input. The seed is
+    /// stored in recordings, so that they can be replayed identically.
+    pub rng: XorShiftRng,
+    /// Whether the next game should be recorded as the demo which the main
+    /// menu plays back, as asked by `--record-demo`.
+    pub record_demo: bool,
     allocated_channels: isize,
     cached_fonts: HashMap<(&'static str, i32), ::sdl2::ttf::Font<'window, 'window>>,
 }
@@ -37,26 +203,55 @@ impl<'window> Phi<'window> {
            renderer: Renderer<'window>,
            ttf_context: &'window Sdl2TtfContext)
            -> Phi<'window> {
-        let allocated_channels = 32 as isize;
+        let allocated_channels = 32_isize;
         ::sdl2::mixer::allocate_channels(allocated_channels as i32);
-        Phi {
+        let mut phi = Phi {
             events: events,
             renderer: renderer,
             ttf_context: ttf_context,
+            rng: XorShiftRng::new_unseeded(),
+            record_demo: false,
             allocated_channels: allocated_channels,
             cached_fonts: HashMap::new(),
-        }
+        };
+        phi.reseed(::rand::random());
+        phi
+    }
+
+    /// Restarts the gameplay randomness from `seed`, so that the same
+    /// sequence of random numbers is produced every time.
+    pub fn reseed(&mut self, seed: u64) {
+        let (lo, hi) = (seed as u32, (seed >> 32) as u32);
+        // XorShift requires a seed which is not only made of zeroes
+        self.rng = XorShiftRng::from_seed([lo, hi, lo ^ 0x9e37_79b9, hi ^ 0x7f4a_7c15]);
     }
     pub fn play_sound(&mut self, sound: &::sdl2::mixer::Chunk) {
-        match ::sdl2::mixer::Channel::all().play(sound, 1) {
-            Err(_) => {
-                self.allocated_channels *= 2;
-                ::sdl2::mixer::allocate_channels(self.allocated_channels as i32);
-                self.play_sound(sound);
-            }
-            _ => {}
+        if ::sdl2::mixer::Channel::all().play(sound, 1).is_err() {
+            self.allocated_channels *= 2;
+            ::sdl2::mixer::allocate_channels(self.allocated_channels as i32);
+            self.play_sound(sound);
         }
     }
+    /// Records the player's input to `path` from the next step on. The
+    /// randomness is reseeded, deterministically, so that the recording
+    /// starts from a known seed.
+    pub fn start_recording(&mut self, path: &Path) -> io::Result<()> {
+        let seed = self.rng.next_u64();
+        let recorder = Recorder::create(path, seed, &self.events.held())?;
+        self.reseed(seed);
+        self.events.record(recorder);
+        Ok(())
+    }
+
+    /// Plays back the recording stored at `path` from the next step on,
+    /// instead of listening to the player.
+    pub fn start_replay(&mut self, path: &Path) -> Result<(), String> {
+        let replay = Replay::load(path)?;
+        self.reseed(replay.seed());
+        self.events.replay(replay);
+        Ok(())
+    }
+
     pub fn output_size(&self) -> (f64, f64) {
         let (w, h) = self.renderer.output_size().unwrap();
         (w as f64, h as f64)
@@ -87,28 +282,183 @@ impl<'window> Phi<'window> {
     }
 }
 
+/// Returns the path of the file called `name` in the directory where the game
+/// stores the user's data, creating that directory if needed. Returns `None`
+/// if the platform offers no such directory.
+pub fn user_file(name: &str) -> Option<PathBuf> {
+    ::sdl2::filesystem::pref_path("alanpoon", "arcaders")
+        .ok()
+        .map(|dir| Path::new(&dir).join(name))
+}
 
 pub enum ViewAction {
     None,
     Quit,
-    ChangeView(Box<View>),
+    /// Puts a view on top of the current one, which is kept alive underneath.
+    /// The covered view is neither exited now, nor entered again once the
+    /// new view is popped.
+    Push(Box<dyn View>),
+    /// Destroys the current view and goes back to the one below it. Quits if
+    /// there is none.
+    Pop,
+    /// Destroys the current view and puts another one in its place.
+    Replace(Box<dyn View>),
+    /// Destroys every view in the stack and starts over from the given one.
+    Reset(Box<dyn View>),
 }
 
+/// Duration of a simulation step, in seconds. The main loop always advances
+/// views by exactly this amount, however long a frame actually took.
+pub const FIXED_DT: f64 = 1.0 / 60.0;
+
+/// Longest frame the main loop will try to catch up with, in seconds. Beyond
+/// that, the simulation slows down instead of spiralling into ever longer
+/// frames.
+const MAX_FRAME_TIME: f64 = 0.25;
+
 pub trait View {
-    /// Called on every frame to take care of both the logic and
-    /// the rendering of the current view.
+    /// Called once, when the view is put in the stack, before its first
+    /// update. It is not called again when the view becomes the current one
+    /// after the views pushed on top of it were popped.
+    fn enter(&mut self, _context: &mut Phi) {}
+
+    /// Called once, when the view is taken out of the stack. Being covered by
+    /// a pushed view does not count, as the view is still alive underneath.
+    fn exit(&mut self, _context: &mut Phi) {}
+
+    /// Called on every simulation step to take care of the logic of the
+    /// current view, without drawing anything.
     ///
-    /// `elapsed` is expressed in seconds.
-    fn render(&mut self, context: &mut Phi, elapsed: f64) -> ViewAction;
+    /// `elapsed` is expressed in seconds, and is always `FIXED_DT`. By
+    /// default, this forwards to `render`, so that views which both simulate
+    /// and draw in a single method keep working.
+    fn update(&mut self, context: &mut Phi, elapsed: f64) -> ViewAction {
+        self.render(context, elapsed)
+    }
+
+    /// Called once per displayed frame, after every pending simulation step.
+    ///
+    /// `alpha`, in `[0, 1)`, is how far the clock has progressed towards the
+    /// next step, and may be used to interpolate positions.
+    fn draw(&self, _context: &mut Phi, _alpha: f64) {}
+
+    /// Single-phase alternative to `update` and `draw`, which takes care of
+    /// both the logic and the rendering of the view on every step. Views
+    /// implementing `update` need not implement it.
+    fn render(&mut self, _context: &mut Phi, _elapsed: f64) -> ViewAction {
+        ViewAction::None
+    }
+
+    /// Whether the view only covers part of the screen, in which case the
+    /// view below it in the stack is drawn first. Only the top-most view is
+    /// ever updated, so the views underneath are frozen.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+/// The views which are currently alive. Only the last one, the current view,
+/// is updated; the others wait for it to be popped.
+struct ViewStack {
+    views: Vec<Box<dyn View>>,
+}
+
+impl ViewStack {
+    fn new(context: &mut Phi, mut view: Box<dyn View>) -> ViewStack {
+        view.enter(context);
+        ViewStack { views: vec![view] }
+    }
+
+    /// Updates the current view and applies the resulting action. Returns
+    /// `false` once the stack is empty, i.e. when the game should quit.
+    fn update(&mut self, context: &mut Phi, elapsed: f64) -> bool {
+        let action = match self.views.last_mut() {
+            Some(view) => view.update(context, elapsed),
+            None => return false,
+        };
+
+        match action {
+            ViewAction::None => {}
+            ViewAction::Quit => {
+                while let Some(mut view) = self.views.pop() {
+                    view.exit(context);
+                }
+            }
+            ViewAction::Push(mut view) => {
+                view.enter(context);
+                self.views.push(view);
+            }
+            ViewAction::Pop => {
+                if let Some(mut view) = self.views.pop() {
+                    view.exit(context);
+                }
+            }
+            ViewAction::Replace(mut view) => {
+                if let Some(mut old) = self.views.pop() {
+                    old.exit(context);
+                }
+                view.enter(context);
+                self.views.push(view);
+            }
+            ViewAction::Reset(mut view) => {
+                while let Some(mut old) = self.views.pop() {
+                    old.exit(context);
+                }
+                view.enter(context);
+                self.views.push(view);
+            }
+        }
+
+        !self.views.is_empty()
+    }
+
+    /// Draws the current view, preceded by every view it overlays.
+    fn draw(&self, context: &mut Phi, alpha: f64) {
+        let mut first = self.views.len();
+        while first > 0 {
+            first -= 1;
+            if !self.views[first].is_overlay() {
+                break;
+            }
+        }
+
+        for view in &self.views[first..] {
+            view.draw(context, alpha);
+        }
+    }
+}
+
+/// Source of time for the main loop. A real window follows the SDL timer,
+/// while a headless run simulates it so that frames are produced as fast as
+/// possible, yet every view observes the same sequence of steps.
+enum Clock {
+    Real(TimerSubsystem),
+    Simulated { ticks: u32 },
+}
+
+impl Clock {
+    fn ticks(&mut self) -> u32 {
+        match *self {
+            Clock::Real(ref mut timer) => timer.ticks(),
+            Clock::Simulated { ticks } => ticks,
+        }
+    }
+
+    fn delay(&mut self, ms: u32) {
+        match *self {
+            Clock::Real(ref mut timer) => timer.delay(ms),
+            Clock::Simulated { ref mut ticks } => *ticks += ms,
+        }
+    }
 }
 
 //2nd argument takes closure, Box<View> to relieve the defaultview
 pub fn spawn<F>(title: &str, init: F)
-    where F: Fn(&mut Phi) -> Box<View>
+    where F: Fn(&mut Phi) -> Box<dyn View>
 {
     let sdl_context = ::sdl2::init().unwrap();
     let video = sdl_context.video().unwrap();
-    let mut timer = sdl_context.timer().unwrap();
+    let timer = sdl_context.timer().unwrap();
     let _image_context = ::sdl2::image::init(::sdl2::image::INIT_PNG).unwrap();
     let _ttf_context = ::sdl2::ttf::init().unwrap();
     let _mixer_context = ::sdl2::mixer::init(::sdl2::mixer::INIT_OGG).unwrap();
@@ -122,7 +472,11 @@ pub fn spawn<F>(title: &str, init: F)
         .build()
         .unwrap();
     // Create Phi Context
-    let mut context = Phi::new(Events::new(sdl_context.event_pump().unwrap()),
+    let mut events = Events::new(sdl_context.event_pump().unwrap(),
+                                 game_controllers(&sdl_context),
+                                 window.size());
+    events.bindings = Bindings::load();
+    let mut context = Phi::new(events,
                                window.renderer()
                                    .accelerated()
                                    .build()
@@ -130,37 +484,274 @@ pub fn spawn<F>(title: &str, init: F)
                                &_ttf_context);
 
     //create default view using a box
-    let mut current_view: Box<View> = init(&mut context);
+    let current_view: Box<dyn View> = init(&mut context);
+    run(&mut context, Clock::Real(timer), None, current_view);
+}
 
-    let interval = 1_000 / 60;
-    let mut before = timer.ticks();
-    let mut last_second = timer.ticks();
-    let mut fps = 0u16;
+/// Same as `spawn`, but without a display: SDL uses its dummy video and audio
+/// drivers, and views are drawn by a software renderer into an offscreen
+/// surface of `width`x`height` pixels. The loop stops after `frames` frames,
+/// unless a view quits earlier.
+pub fn spawn_headless<F>(width: u32, height: u32, frames: u32, init: F)
+    where F: Fn(&mut Phi) -> Box<dyn View>
+{
+    // Must be set before SDL is initialized to have any effect.
+    ::std::env::set_var("SDL_VIDEODRIVER", "dummy");
+    ::std::env::set_var("SDL_AUDIODRIVER", "dummy");
+
+    let sdl_context = ::sdl2::init().unwrap();
+    // The event pump requires the video subsystem, even without a window.
+    let _video = sdl_context.video().unwrap();
+    let _image_context = ::sdl2::image::init(::sdl2::image::INIT_PNG).unwrap();
+    let _ttf_context = ::sdl2::ttf::init().unwrap();
+    let _mixer_context = ::sdl2::mixer::init(::sdl2::mixer::INIT_OGG).unwrap();
+    ::sdl2::mixer::open_audio(44100, ::sdl2::mixer::AUDIO_S16LSB, 2, 1024).unwrap();
+    ::sdl2::mixer::allocate_channels(32);
+    // Create the offscreen target
+    let surface = Surface::new(width, height, PixelFormatEnum::RGBA8888).unwrap();
+    let events = Events::new(sdl_context.event_pump().unwrap(),
+                             game_controllers(&sdl_context),
+                             (width, height));
+    let mut context = Phi::new(events,
+                               Renderer::from_surface(surface).unwrap(),
+                               &_ttf_context);
 
-    // Prepare the events record
+    let current_view: Box<dyn View> = init(&mut context);
+    run(&mut context, Clock::Simulated { ticks: 0 }, Some(frames), current_view);
+}
+
+/// Enables game controllers, or returns `None` if SDL cannot, e.g. on a
+/// system without joystick support, in which case the game is played with
+/// the keyboard alone.
+fn game_controllers(sdl_context: &Sdl) -> Option<GameControllerSubsystem> {
+    match sdl_context.game_controller() {
+        Ok(controllers) => Some(controllers),
+        Err(e) => {
+            println!("Game controllers are unavailable: {}", e);
+            None
+        }
+    }
+}
+
+/// The main loop shared by `spawn` and `spawn_headless`.
+///
+/// Time is accumulated every frame and consumed in steps of `FIXED_DT`, so
+/// that the simulation does not depend on the frame rate. Events are pumped
+/// once per step, which guarantees that a key press is seen exactly once.
+fn run(context: &mut Phi, mut clock: Clock, max_frames: Option<u32>, first_view: Box<dyn View>) {
+    let mut views = ViewStack::new(context, first_view);
+    let mut before = clock.ticks();
+    let mut last_second = before;
+    let mut accumulator = 0.0;
+    let mut fps = 0u16;
+    let mut frames = 0u32;
 
     loop {
-        let now = timer.ticks();
-        let dt = now - before;
-        let elapsed = dt as f64 / 1_000.0;
-        if dt < interval {
-            timer.delay(interval - dt);
-            continue;
+        if max_frames.is_some_and(|max| frames >= max) {
+            break;
         }
+        let now = clock.ticks();
+        let frame_time = context.events.frame_time(now - before) as f64 / 1_000.0;
         before = now;
+        accumulator += frame_time.min(MAX_FRAME_TIME);
+
+        // Simulate every step which is due
+        while accumulator >= FIXED_DT {
+            accumulator -= FIXED_DT;
+            context.events.pump();
+            if !views.update(context, FIXED_DT) {
+                return;
+            }
+        }
+
+        views.draw(context, accumulator / FIXED_DT);
+        context.renderer.present();
         fps += 1;
+        frames += 1;
+
         if now - last_second > 1_000 {
-            println!("FPS: {}", fps);
+            // A simulated clock runs as fast as it can, so its frame rate
+            // means nothing and would only clutter the output
+            if let Clock::Real(_) = clock {
+                println!("FPS: {}", fps);
+            }
             last_second = now;
             fps = 0;
         }
 
-        context.events.pump(&mut context.renderer);
-        match current_view.render(&mut context, elapsed) {
-            ViewAction::None => context.renderer.present(),
-            ViewAction::Quit => break,
-            ViewAction::ChangeView(new_view) => current_view = new_view,
+        // Sleep until the next step is due
+        let wait = ((FIXED_DT - accumulator) * 1_000.0).ceil();
+        if wait > 0.0 {
+            clock.delay(wait as u32);
         }
     }
+}
+
+/// SDL may only be initialized once at a time, while tests run in parallel.
+/// Tests which need SDL hold this lock for as long as they use it.
+#[cfg(test)]
+pub fn lock_sdl() -> ::std::sync::MutexGuard<'static, ()> {
+    static SDL_LOCK: ::std::sync::Mutex<()> = ::std::sync::Mutex::new(());
+    ::std::env::set_var("SDL_VIDEODRIVER", "dummy");
+    // A failed test poisons the lock, which must not fail the others
+    SDL_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::controller::Button;
+    use sdl2::event::Event;
+    use sdl2::keyboard::{Keycode, NOMOD};
+
+    fn key_down(keycode: Keycode, repeat: bool) -> Event {
+        Event::KeyDown {
+            timestamp: 0,
+            window_id: 0,
+            keycode: Some(keycode),
+            scancode: None,
+            keymod: NOMOD,
+            repeat: repeat,
+        }
+    }
+
+    fn key_up(keycode: Keycode) -> Event {
+        Event::KeyUp {
+            timestamp: 0,
+            window_id: 0,
+            keycode: Some(keycode),
+            scancode: None,
+            keymod: NOMOD,
+            repeat: false,
+        }
+    }
+
+    /// Feeds `events` to `handle` as a single step.
+    fn step(events: &mut Events, step: Vec<Event>) {
+        events.now = ImmediateEvents::new();
+        for event in step {
+            events.handle(event);
+        }
+        events.refresh();
+    }
+
+    #[test]
+    fn keys_are_pressed_once_and_released() {
+        let _lock = lock_sdl();
+        let sdl = ::sdl2::init().unwrap();
+        let _video = sdl.video().unwrap();
+        // The keyboard works without game controller support
+        let mut events = Events::new(sdl.event_pump().unwrap(), None, (800, 600));
+
+        step(&mut events, vec![key_down(Keycode::Space, false)]);
+        assert!(events.key_space);
+        assert_eq!(events.now.key_space, Some(true));
+        assert_eq!(events.now.pressed, Some(Input::Key(Keycode::Space)));
+
+        // Holding the key down repeats it, which is not a new press
+        step(&mut events, vec![key_down(Keycode::Space, true)]);
+        assert!(events.key_space);
+        assert_eq!(events.now.key_space, None);
+
+        step(&mut events, vec![key_up(Keycode::Space)]);
+        assert!(!events.key_space);
+        assert_eq!(events.now.key_space, Some(false));
+    }
+
+    #[test]
+    fn removing_a_controller_only_releases_its_buttons() {
+        let _lock = lock_sdl();
+        let sdl = ::sdl2::init().unwrap();
+        let _video = sdl.video().unwrap();
+        let mut events = Events::new(sdl.event_pump().unwrap(), sdl.game_controller().ok(), (800, 600));
 
+        step(&mut events,
+             vec![Event::ControllerButtonDown { timestamp: 0, which: 1, button: Button::A },
+                  Event::ControllerButtonDown { timestamp: 0, which: 2, button: Button::A }]);
+        assert!(events.key_space);
+
+        step(&mut events, vec![Event::ControllerDeviceRemoved { timestamp: 0, which: 1 }]);
+        assert!(events.key_space);
+        assert_eq!(events.now.key_space, None);
+
+        step(&mut events, vec![Event::ControllerDeviceRemoved { timestamp: 0, which: 2 }]);
+        assert!(!events.key_space);
+        assert_eq!(events.now.key_space, Some(false));
+    }
+
+    #[test]
+    fn the_dead_zone_is_ignored() {
+        let _lock = lock_sdl();
+        let sdl = ::sdl2::init().unwrap();
+        let _video = sdl.video().unwrap();
+        let mut events = Events::new(sdl.event_pump().unwrap(), sdl.game_controller().ok(), (800, 600));
+        let axis = |axis, value| Event::ControllerAxisMotion { timestamp: 0, which: 1, axis: axis, value: value };
+
+        step(&mut events, vec![axis(Axis::LeftX, 4_000)]);
+        assert_eq!(events.stick, (0.0, 0.0));
+
+        step(&mut events, vec![axis(Axis::LeftX, 32_767)]);
+        assert!((events.stick.0 - 1.0).abs() < 1e-9);
+
+        step(&mut events, vec![Event::ControllerDeviceRemoved { timestamp: 0, which: 1 }]);
+        assert_eq!(events.stick, (0.0, 0.0));
+    }
+
+    #[test]
+    fn bindings_survive_a_round_trip() {
+        let bindings = Bindings {
+            // Keys whose names need quoting or escaping, or hold spaces
+            key_up: vec![Input::Key(Keycode::Comma),
+                         Input::Key(Keycode::Quotedbl),
+                         Input::Key(Keycode::Backslash),
+                         Input::Key(Keycode::LShift),
+                         Input::Button(Button::A)],
+            // Every input of a key may be unbound
+            key_space: vec![],
+            dead_zone: 0.3,
+            ..Bindings::default()
+        };
+        let mut saved = vec![];
+        bindings.write(&mut saved).unwrap();
+        let saved = String::from_utf8(saved).unwrap();
+
+        let mut lines = saved.lines();
+        assert_eq!(lines.next(), Some(BINDINGS_HEADER));
+        let mut loaded = Bindings::default();
+        for line in lines {
+            loaded.parse_line(line).unwrap();
+        }
+        for name in Bindings::names() {
+            assert_eq!(loaded.get(name), bindings.get(name), "{}", name);
+        }
+        assert_eq!(loaded.dead_zone, 0.3);
+    }
+
+    #[test]
+    fn inputs_are_quoted_and_escaped() {
+        assert_eq!(quote("Left Shift"), "\"Left Shift\"");
+        assert_eq!(quote("\""), "\"\\\"\"");
+        assert_eq!(quote("\\"), "\"\\\\\"");
+        assert_eq!(parse_inputs(" \"Up\" ,\"pad:a\""),
+                   Ok(vec![Input::Key(Keycode::Up), Input::Button(Button::A)]));
+        assert_eq!(parse_inputs("\",\", \"\\\"\""),
+                   Ok(vec![Input::Key(Keycode::Comma), Input::Key(Keycode::Quotedbl)]));
+    }
+
+    #[test]
+    fn malformed_bindings_are_rejected() {
+        let mut bindings = Bindings::default();
+        assert_eq!(bindings.parse_line("  # A comment"), Ok(()));
+        assert_eq!(bindings.parse_line(""), Ok(()));
+        for line in &["key_up", "key_up = Up", "key_up = \"Up", "key_up = \"Up\" \"W\"",
+                      "key_up = \"Up\",", "key_up = \"Nowhere\"", "key_jump = \"Up\"",
+                      "dead_zone = 1", "dead_zone = -0.1", "dead_zone = some"] {
+            assert!(bindings.parse_line(line).is_err(), "`{}` should be rejected", line);
+        }
+        // Nothing changed
+        for name in Bindings::names() {
+            assert_eq!(bindings.get(name), Bindings::default().get(name), "{}", name);
+        }
+        assert_eq!(bindings.dead_zone, Bindings::default().dead_zone);
+    }
 }
@@ -0,0 +1,399 @@
+//! Recording of the player's input, and playback of such recordings.
+//!
+//! A recording starts with `MAGIC`, a version byte, the seed of the gameplay
+//! randomness, stored as a little-endian `u64`, and the input held when the
+//! recording started, stored as a step's payload. What follows is a
+//! sequence of records, each made of a tag byte, a little-endian `u16` repeat
+//! count, and a payload:
+//!
+//! * `F` is followed by the duration of a frame, in milliseconds, as a `u16`;
+//! * `S` is followed by the logical input of a simulation step (see
+//!   `StepInput::write`).
+//!
+//! Identical consecutive frames or steps are stored once along with how many
+//! times they repeat, which keeps recordings of idle periods tiny.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8] = b"ARCR";
+/// Bump whenever the format changes, so that older recordings are rejected
+/// instead of misread.
+const VERSION: u8 = 3;
+const FRAME_TAG: u8 = b'F';
+const STEP_TAG: u8 = b'S';
+/// Size of a step's payload, in bytes.
+const STEP_LEN: usize = 19;
+
+/// Scale applied to the analog stick, whose axes are in [-1, 1], to store it
+/// as an integer.
+const STICK_SCALE: f64 = 32_767.0;
+
+pub const MOUSE_LEFT: u8 = 1 << 0;
+pub const MOUSE_MIDDLE: u8 = 1 << 1;
+pub const MOUSE_RIGHT: u8 = 1 << 2;
+pub const MOUSE_MOVED: u8 = 1 << 3;
+pub const MOUSE_CLICKED: u8 = 1 << 4;
+
+/// The logical input of a single simulation step, quantized so that it can be
+/// stored exactly.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct StepInput {
+    /// One bit per logical key, in the order they were declared, set while
+    /// the key is held down.
+    pub keys: u32,
+    /// A combination of the `MOUSE_*` flags.
+    pub mouse_flags: u8,
+    stick: (i16, i16),
+    mouse: (i16, i16),
+    click: (i16, i16),
+    pub wheel: (i8, i8),
+}
+
+impl StepInput {
+    pub fn stick(&self) -> (f64, f64) {
+        (self.stick.0 as f64 / STICK_SCALE, self.stick.1 as f64 / STICK_SCALE)
+    }
+
+    pub fn set_stick(&mut self, stick: (f64, f64)) {
+        self.stick = ((stick.0 * STICK_SCALE) as i16, (stick.1 * STICK_SCALE) as i16);
+    }
+
+    pub fn mouse(&self) -> (f64, f64) {
+        (self.mouse.0 as f64, self.mouse.1 as f64)
+    }
+
+    pub fn set_mouse(&mut self, mouse: (f64, f64)) {
+        self.mouse = (mouse.0 as i16, mouse.1 as i16);
+    }
+
+    pub fn click(&self) -> Option<(f64, f64)> {
+        if self.mouse_flags & MOUSE_CLICKED != 0 {
+            Some((self.click.0 as f64, self.click.1 as f64))
+        } else {
+            None
+        }
+    }
+
+    pub fn set_click(&mut self, click: Option<(f64, f64)>) {
+        match click {
+            Some((x, y)) => {
+                self.mouse_flags |= MOUSE_CLICKED;
+                self.click = (x as i16, y as i16);
+            }
+            None => {
+                self.mouse_flags &= !MOUSE_CLICKED;
+                self.click = (0, 0);
+            }
+        }
+    }
+
+    /// Serializes the step as `keys: u32`, `mouse_flags: u8`,
+    /// then the stick, the mouse and the click as pairs of `i16`, and finally
+    /// the wheel as a pair of `i8`.
+    fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(&self.keys.to_le_bytes())?;
+        out.write_all(&[self.mouse_flags])?;
+        for &(x, y) in &[self.stick, self.mouse, self.click] {
+            out.write_all(&x.to_le_bytes())?;
+            out.write_all(&y.to_le_bytes())?;
+        }
+        out.write_all(&[self.wheel.0 as u8, self.wheel.1 as u8])
+    }
+
+    fn read(bytes: &[u8]) -> StepInput {
+        let i16_at = |i: usize| i16::from_le_bytes([bytes[i], bytes[i + 1]]);
+        StepInput {
+            keys: u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            mouse_flags: bytes[4],
+            stick: (i16_at(5), i16_at(7)),
+            mouse: (i16_at(9), i16_at(11)),
+            click: (i16_at(13), i16_at(15)),
+            wheel: (bytes[17] as i8, bytes[18] as i8),
+        }
+    }
+}
+
+/// Writes the input of every step, and the duration of every frame, to a file
+/// as the game runs.
+pub struct Recorder {
+    out: BufWriter<File>,
+    /// The last frame duration, and how many times in a row it happened.
+    frames: Option<(u16, u16)>,
+    /// The last step, and how many times in a row it happened.
+    steps: Option<(StepInput, u16)>,
+}
+
+impl Recorder {
+    /// Starts a recording at `path`. `held` is the input held down when the
+    /// recording starts, so that a replay starts in the same state instead
+    /// of seeing fresh presses.
+    pub fn create(path: &Path, seed: u64, held: &StepInput) -> io::Result<Recorder> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(MAGIC)?;
+        out.write_all(&[VERSION])?;
+        out.write_all(&seed.to_le_bytes())?;
+        held.write(&mut out)?;
+        Ok(Recorder {
+            out: out,
+            frames: None,
+            steps: None,
+        })
+    }
+
+    pub fn frame(&mut self, ms: u32) -> io::Result<()> {
+        let ms = ms.min(u16::MAX as u32) as u16;
+        match self.frames {
+            Some((last, ref mut count)) if last == ms && *count < u16::MAX => {
+                *count += 1;
+                return Ok(());
+            }
+            _ => {}
+        }
+        self.flush_frames()?;
+        self.frames = Some((ms, 1));
+        Ok(())
+    }
+
+    pub fn step(&mut self, input: &StepInput) -> io::Result<()> {
+        match self.steps {
+            Some((ref last, ref mut count)) if last == input && *count < u16::MAX => {
+                *count += 1;
+                return Ok(());
+            }
+            _ => {}
+        }
+        self.flush_steps()?;
+        self.steps = Some((*input, 1));
+        Ok(())
+    }
+
+    /// Writes whatever is still pending, and closes the file.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.flush_frames()?;
+        self.flush_steps()?;
+        self.out.flush()
+    }
+
+    fn flush_frames(&mut self) -> io::Result<()> {
+        if let Some((ms, count)) = self.frames.take() {
+            self.out.write_all(&[FRAME_TAG])?;
+            self.out.write_all(&count.to_le_bytes())?;
+            self.out.write_all(&ms.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn flush_steps(&mut self) -> io::Result<()> {
+        if let Some((input, count)) = self.steps.take() {
+            self.out.write_all(&[STEP_TAG])?;
+            self.out.write_all(&count.to_le_bytes())?;
+            input.write(&mut self.out)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Recorder {
+    /// Keeps what was recorded so far if the game stops unexpectedly, e.g.
+    /// because of a panic, which is precisely when a recording is useful.
+    fn drop(&mut self) {
+        let _ = self.flush_frames();
+        let _ = self.flush_steps();
+    }
+}
+
+/// A recording being played back.
+pub struct Replay {
+    seed: u64,
+    held: StepInput,
+    frames: VecDeque<(u16, u16)>,
+    steps: VecDeque<(StepInput, u16)>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let mut bytes = vec![];
+        File::open(path)
+            .and_then(|mut file| file.read_to_end(&mut bytes))
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Replay::parse(&bytes).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Replay, String> {
+        let seed_end = MAGIC.len() + 1 + 8;
+        let header_len = seed_end + STEP_LEN;
+        if bytes.len() < header_len || &bytes[..MAGIC.len()] != MAGIC {
+            return Err("not a recording".to_string());
+        }
+        if bytes[MAGIC.len()] != VERSION {
+            return Err(format!("unsupported recording version {}", bytes[MAGIC.len()]));
+        }
+        let mut seed = [0; 8];
+        seed.copy_from_slice(&bytes[MAGIC.len() + 1..seed_end]);
+
+        let mut replay = Replay {
+            seed: u64::from_le_bytes(seed),
+            held: StepInput::read(&bytes[seed_end..header_len]),
+            frames: VecDeque::new(),
+            steps: VecDeque::new(),
+        };
+        let mut at = header_len;
+        while at < bytes.len() {
+            let payload_len = match bytes[at] {
+                FRAME_TAG => 2,
+                STEP_TAG => STEP_LEN,
+                tag => return Err(format!("unknown record type {:#04x} at byte {}", tag, at)),
+            };
+            if at + 3 + payload_len > bytes.len() {
+                return Err(format!("truncated record at byte {}", at));
+            }
+            let count = u16::from_le_bytes([bytes[at + 1], bytes[at + 2]]);
+            if count == 0 {
+                return Err(format!("empty record at byte {}", at));
+            }
+            let payload = &bytes[at + 3..at + 3 + payload_len];
+            if bytes[at] == FRAME_TAG {
+                replay.frames.push_back((u16::from_le_bytes([payload[0], payload[1]]), count));
+            } else {
+                replay.steps.push_back((StepInput::read(payload), count));
+            }
+            at += 3 + payload_len;
+        }
+        Ok(replay)
+    }
+
+    /// The seed of the gameplay randomness when the recording started.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The input held down when the recording started.
+    pub fn held(&self) -> StepInput {
+        self.held
+    }
+
+    /// The duration of the next recorded frame, in milliseconds.
+    pub fn next_frame(&mut self) -> Option<u32> {
+        next(&mut self.frames).map(|ms| ms as u32)
+    }
+
+    /// The input of the next recorded step, or `None` once the recording is
+    /// over.
+    pub fn next_step(&mut self) -> Option<StepInput> {
+        next(&mut self.steps)
+    }
+}
+
+/// Pops a single element from a queue of repeated elements.
+fn next<T: Copy>(queue: &mut VecDeque<(T, u16)>) -> Option<T> {
+    let (value, count) = match queue.front_mut() {
+        Some(&mut (value, ref mut count)) => {
+            *count -= 1;
+            (value, *count)
+        }
+        None => return None,
+    };
+    if count == 0 {
+        queue.pop_front();
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn input(keys: u32) -> StepInput {
+        StepInput { keys: keys, ..StepInput::default() }
+    }
+
+    fn header(version: u8) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(version);
+        bytes.extend_from_slice(&42u64.to_le_bytes());
+        input(0).write(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn steps_survive_a_round_trip() {
+        let mut held = input(0b101);
+        held.set_stick((0.5, -1.0));
+        let mut busy = input(0b10);
+        busy.mouse_flags = MOUSE_LEFT | MOUSE_MOVED;
+        busy.set_mouse((640.0, -20.0));
+        busy.set_click(Some((3.0, 4.0)));
+        busy.wheel = (-3, 127);
+
+        let path = ::std::env::temp_dir()
+            .join(format!("arcaders-replay-{}.rec", ::std::process::id()));
+        let mut recorder = Recorder::create(&path, 0xdead_beef_1234, &held).unwrap();
+        for &(ms, step) in &[(16, input(0)), (16, input(0)), (17, busy), (100_000, input(0))] {
+            recorder.frame(ms).unwrap();
+            recorder.step(&step).unwrap();
+        }
+        recorder.finish().unwrap();
+        let mut replay = Replay::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(replay.seed(), 0xdead_beef_1234);
+        assert_eq!(replay.held(), held);
+        assert_eq!(replay.held().stick(), (16_383.0 / STICK_SCALE, -1.0));
+        let frames: Vec<u32> = ::std::iter::from_fn(|| replay.next_frame()).collect();
+        // Frames too long to be stored are clamped
+        assert_eq!(frames, vec![16, 16, 17, u16::MAX as u32]);
+        let steps: Vec<StepInput> = ::std::iter::from_fn(|| replay.next_step()).collect();
+        assert_eq!(steps, vec![input(0), input(0), busy, input(0)]);
+        assert_eq!(steps[2].click(), Some((3.0, 4.0)));
+        assert_eq!(steps[2].mouse(), (640.0, -20.0));
+    }
+
+    #[test]
+    fn repeated_records_are_stored_once() {
+        let mut bytes = header(VERSION);
+        bytes.extend_from_slice(&[FRAME_TAG, 3, 0, 16, 0]);
+        bytes.push(STEP_TAG);
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        input(1).write(&mut bytes).unwrap();
+
+        let mut replay = Replay::parse(&bytes).unwrap();
+        assert_eq!(replay.seed(), 42);
+        assert_eq!((replay.next_frame(), replay.next_frame(), replay.next_frame()),
+                   (Some(16), Some(16), Some(16)));
+        assert_eq!(replay.next_frame(), None);
+        assert_eq!((replay.next_step(), replay.next_step()), (Some(input(1)), Some(input(1))));
+        assert_eq!(replay.next_step(), None);
+    }
+
+    #[test]
+    fn invalid_recordings_are_rejected() {
+        assert_eq!(Replay::parse(b"").err(), Some("not a recording".to_string()));
+        assert_eq!(Replay::parse(&header(VERSION)[..MAGIC.len() + 9]).err(),
+                   Some("not a recording".to_string()));
+        let mut bytes = header(VERSION);
+        bytes[0] = b'X';
+        assert_eq!(Replay::parse(&bytes).err(), Some("not a recording".to_string()));
+        assert_eq!(Replay::parse(&header(1)).err(),
+                   Some("unsupported recording version 1".to_string()));
+        assert!(Replay::parse(&header(VERSION)).is_ok());
+
+        let header_len = header(VERSION).len();
+        let with = |record: &[u8]| {
+            let mut bytes = header(VERSION);
+            bytes.extend_from_slice(record);
+            Replay::parse(&bytes).err()
+        };
+        assert_eq!(with(&[b'X', 1, 0]),
+                   Some(format!("unknown record type 0x58 at byte {}", header_len)));
+        assert_eq!(with(&[FRAME_TAG, 1, 0, 16]),
+                   Some(format!("truncated record at byte {}", header_len)));
+        assert_eq!(with(&[STEP_TAG, 1, 0, 0, 0]),
+                   Some(format!("truncated record at byte {}", header_len)));
+        assert_eq!(with(&[FRAME_TAG, 0, 0, 16, 0]),
+                   Some(format!("empty record at byte {}", header_len)));
+    }
+}
@@ -0,0 +1,309 @@
+use phi::Phi;
+use phi::data::{Rectangle, Shape};
+use phi::gfx::Sprite;
+use sdl2::pixels::Color;
+use std::f64::consts::PI;
+use views::bullets::{AimedBullet, Bullet};
+
+const HULL_PATH: &str = "assets/spaceship.png";
+const HULL_W: f64 = 43.0;
+const HULL_H: f64 = 39.0;
+/// The hull is drawn this many times larger than the player's ship.
+const HULL_SCALE: f64 = 4.0;
+const TURRET_W: f64 = 36.0;
+const TURRET_H: f64 = 24.0;
+/// Pixels traveled horizontally every second while entering the screen.
+const ENTER_SPEED: f64 = 80.0;
+/// Distance kept between the boss and the right of the screen.
+const RIGHT_MARGIN: f64 = 40.0;
+/// How far the boss moves up and down, relative to the free space around it.
+const BOB_AMPLITUDE: f64 = 0.8;
+const BULLET_SPEED: f64 = 220.0;
+/// Seconds during which a part flashes after being hit.
+const FLASH_DURATION: f64 = 0.08;
+
+/// What a level says about its boss.
+#[derive(Clone, Debug)]
+pub struct BossDescr {
+    /// Seconds since the start of the game after which the boss appears.
+    /// The waves stop at that point.
+    pub time: f64,
+    /// Hit points of the core, which may only be damaged once the turrets
+    /// are gone.
+    pub health: f64,
+    /// Hit points of each turret.
+    pub turret_health: f64,
+}
+
+impl Default for BossDescr {
+    fn default() -> BossDescr {
+        BossDescr {
+            time: 120.0,
+            health: 60.0,
+            turret_health: 20.0,
+        }
+    }
+}
+
+/// A damageable piece of the boss.
+struct Part {
+    /// Relative to the top-left corner of the boss.
+    offset: Rectangle,
+    health: f64,
+    /// Whether the core, which is shielded while any turret stands.
+    core: bool,
+    /// Seconds left to flash after a hit.
+    flash: f64,
+}
+
+impl Part {
+    fn is_alive(&self) -> bool {
+        self.health > 0.0
+    }
+}
+
+/// What happened to the boss when a bullet was checked against it.
+pub enum Hit {
+    /// The bullet missed every part.
+    Miss,
+    /// The bullet hit the core while it was shielded.
+    Blocked,
+    /// The bullet hit a part, which may have survived.
+    Damaged,
+    /// The bullet destroyed a turret.
+    TurretDestroyed,
+    /// The bullet destroyed the core, and therefore the boss.
+    Destroyed,
+}
+
+/// A large enemy made of a core protected by turrets. Its bullet patterns get
+/// fiercer as it loses its turrets.
+pub struct Boss {
+    /// Top-left corner of the boss.
+    pos: (f64, f64),
+    parts: Vec<Part>,
+    max_health: f64,
+    /// Seconds since the boss appeared.
+    time: f64,
+    /// How far the boss is through its up and down motion, in radians.
+    bob: f64,
+    /// Seconds until the next volley.
+    fire_in: f64,
+    hull: Sprite,
+}
+
+impl Boss {
+    pub fn new(phi: &mut Phi, descr: &BossDescr) -> Boss {
+        let (win_w, win_h) = phi.output_size();
+        let (w, h) = (HULL_W * HULL_SCALE, HULL_H * HULL_SCALE);
+        let spritesheet = Sprite::load(&phi.renderer, HULL_PATH).unwrap();
+        let turret = |y: f64| {
+            Part {
+                offset: Rectangle {
+                    x: -TURRET_W / 2.0,
+                    y: y,
+                    w: TURRET_W,
+                    h: TURRET_H,
+                },
+                health: descr.turret_health,
+                core: false,
+                flash: 0.0,
+            }
+        };
+
+        Boss {
+            pos: (win_w, (win_h - h) / 2.0),
+            parts: vec![Part {
+                            offset: Rectangle {
+                                x: 0.0,
+                                y: 0.0,
+                                w: w,
+                                h: h,
+                            },
+                            health: descr.health,
+                            core: true,
+                            flash: 0.0,
+                        },
+                        turret(h * 0.1),
+                        turret(h * 0.9 - TURRET_H)],
+            max_health: descr.health + 2.0 * descr.turret_health,
+            time: 0.0,
+            bob: 0.0,
+            fire_in: 2.0,
+            hull: spritesheet.region(Rectangle {
+                                         w: HULL_W,
+                                         h: HULL_H,
+                                         x: HULL_W,
+                                         y: HULL_H,
+                                     })
+                .unwrap(),
+        }
+    }
+
+    /// The health left, from 0 to 1.
+    pub fn health(&self) -> f64 {
+        self.parts.iter().map(|part| part.health.max(0.0)).sum::<f64>() / self.max_health
+    }
+
+    /// 1, 2 or 3. The boss gets into the second phase once it has lost a
+    /// turret, and into the last one once its core is exposed. The first
+    /// phase fires from the turrets, and the others from the core, so that
+    /// the boss never runs out of guns whatever its health.
+    pub fn phase(&self) -> u32 {
+        let turrets = self.parts.iter().filter(|part| !part.core);
+        let standing = turrets.clone().filter(|part| part.is_alive()).count();
+        if standing == turrets.count() {
+            1
+        } else if standing > 0 {
+            2
+        } else {
+            3
+        }
+    }
+
+    /// The hitboxes of the parts which are still standing.
+    pub fn hitboxes(&self) -> Vec<Rectangle> {
+        self.parts.iter().filter(|part| part.is_alive()).map(|part| self.rect(part)).collect()
+    }
+
+    fn rect(&self, part: &Part) -> Rectangle {
+        Rectangle {
+            x: self.pos.0 + part.offset.x,
+            y: self.pos.1 + part.offset.y,
+            ..part.offset
+        }
+    }
+
+    /// Moves the boss, and fires at `target`, the center of the player's ship
+    /// if it is alive.
+    pub fn update(&mut self,
+                  phi: &mut Phi,
+                  dt: f64,
+                  target: Option<(f64, f64)>,
+                  bullets: &mut Vec<Box<dyn Bullet>>) {
+        self.time += dt;
+        for part in &mut self.parts {
+            part.flash = (part.flash - dt).max(0.0);
+        }
+
+        // Enter the screen, then bob up and down, faster in later phases
+        let (win_w, win_h) = phi.output_size();
+        let (w, h) = (self.parts[0].offset.w, self.parts[0].offset.h);
+        let rest_x = win_w - w - RIGHT_MARGIN;
+        if self.pos.0 > rest_x {
+            self.pos.0 = (self.pos.0 - ENTER_SPEED * dt).max(rest_x);
+            return;
+        }
+        let free = (win_h - h) / 2.0;
+        let frequency = 0.1 * self.phase() as f64;
+        self.bob += 2.0 * PI * frequency * dt;
+        self.pos.1 = free + free * BOB_AMPLITUDE * self.bob.sin();
+
+        self.fire_in -= dt;
+        if self.fire_in > 0.0 {
+            return;
+        }
+        let target = match target {
+            Some(target) => target,
+            None => {
+                self.fire_in = 0.5;
+                return;
+            }
+        };
+        let core = self.rect(&self.parts[0]).center();
+        let turrets: Vec<(f64, f64)> = self.parts
+            .iter()
+            .filter(|part| !part.core && part.is_alive())
+            .map(|part| self.rect(part).center())
+            .collect();
+        match self.phase() {
+            // Aimed shots from every turret
+            1 => {
+                for &turret in &turrets {
+                    bullets.push(Box::new(AimedBullet::new(turret, target, BULLET_SPEED)));
+                }
+                self.fire_in = 1.2;
+            }
+            // Fans of five bullets from the core
+            2 => {
+                let aim = (target.1 - core.1).atan2(target.0 - core.0);
+                for i in -2..3 {
+                    bullets.push(Box::new(AimedBullet::new(core,
+                                                           towards(core, aim + i as f64 * PI / 12.0),
+                                                           BULLET_SPEED)));
+                }
+                self.fire_in = 1.5;
+            }
+            // Rings of bullets, slowly rotating
+            _ => {
+                let count = 12;
+                for i in 0..count {
+                    let angle = 2.0 * PI * i as f64 / count as f64 + self.time;
+                    bullets.push(Box::new(AimedBullet::new(core,
+                                                           towards(core, angle),
+                                                           BULLET_SPEED * 0.8)));
+                }
+                self.fire_in = 1.8;
+            }
+        }
+    }
+
+    /// Checks whether `bullet`, which moved by `motion` since the last
+    /// check, hit a part of the boss, and takes `damage` hit points from it
+    /// if so. The core is shielded while any turret stands, but still stops
+    /// bullets.
+    pub fn hit(&mut self, bullet: Rectangle, motion: (f64, f64), damage: u32) -> Hit {
+        let shielded = self.parts.iter().any(|part| !part.core && part.is_alive());
+        // Turrets are checked first, as they stand in front of the core
+        let pos = self.pos;
+        for part in self.parts.iter_mut().rev() {
+            let rect = Rectangle {
+                x: pos.0 + part.offset.x,
+                y: pos.1 + part.offset.y,
+                ..part.offset
+            };
+            if !part.is_alive() || !Shape::Rect(rect).hit_by(bullet, motion) {
+                continue;
+            }
+            if part.core && shielded {
+                return Hit::Blocked;
+            }
+            part.health -= damage as f64;
+            part.flash = FLASH_DURATION;
+            return match (part.is_alive(), part.core) {
+                (true, _) => Hit::Damaged,
+                (false, false) => Hit::TurretDestroyed,
+                (false, true) => Hit::Destroyed,
+            };
+        }
+        Hit::Miss
+    }
+
+    pub fn render(&self, phi: &mut Phi) {
+        let core = self.rect(&self.parts[0]);
+        self.hull.render_ex(&mut phi.renderer, core, 0.0, true);
+        for part in &self.parts[1..] {
+            if !part.is_alive() {
+                continue;
+            }
+            let color = if part.flash > 0.0 {
+                Color::RGB(255, 255, 255)
+            } else {
+                Color::RGB(120, 120, 140)
+            };
+            phi.renderer.set_draw_color(color);
+            let _ = phi.renderer.fill_rect(self.rect(part).to_sdl().unwrap());
+        }
+        // Once exposed, the core flashes when hit
+        if self.parts[0].flash > 0.0 {
+            let flash = Rectangle::with_size(core.w / 4.0, core.h / 4.0).center_at(core.center());
+            phi.renderer.set_draw_color(Color::RGB(255, 255, 255));
+            let _ = phi.renderer.fill_rect(flash.to_sdl().unwrap());
+        }
+    }
+}
+
+/// A point in the direction of `angle`, in radians, as seen from `from`.
+fn towards(from: (f64, f64), angle: f64) -> (f64, f64) {
+    (from.0 + angle.cos(), from.1 + angle.sin())
+}
@@ -3,10 +3,10 @@ use phi::data::Rectangle;
 use sdl2::pixels::Color;
 
 
-/// Pixels traveled horizontally by a bullet every second.
-pub const BULLET_SPEED: f64 = 240.0;
 pub const BULLET_W: f64 = 8.0;
 pub const BULLET_H: f64 = 4.0;
+/// Hit points taken by a charge shot, enough to destroy any asteroid at once.
+const CHARGE_DAMAGE: u32 = 5;
 
 
 pub trait Bullet {
@@ -14,24 +14,53 @@ pub trait Bullet {
     /// If the bullet should be destroyed, e.g. because it has left the screen,
     /// then return `None`.
     /// Otherwise, return `Some(update_bullet)`.
-    fn update(self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<Bullet>>;
+    fn update(self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<dyn Bullet>>;
 
     /// Render the bullet to the screen.
     fn render(&self, phi: &mut Phi);
 
     /// Return the bullet's bounding box.
     fn rect(&self) -> Rectangle;
+
+    /// Return how far the bullet moved during the last update, so that
+    /// collisions along the way are not missed when it moves fast.
+    fn motion(&self) -> (f64, f64);
+
+    /// Return how many hit points the bullet takes from what it hits.
+    fn damage(&self) -> u32 {
+        1
+    }
 }
 
 
 pub struct RectBullet {
     rect: Rectangle,
+    /// Pixels traveled horizontally every second.
+    speed: f64,
+    moved: (f64, f64),
+}
+
+impl RectBullet {
+    /// A bullet whose top-left corner is at `(x, y)`.
+    pub fn new(x: f64, y: f64, speed: f64) -> RectBullet {
+        RectBullet {
+            rect: Rectangle {
+                x: x,
+                y: y,
+                w: BULLET_W,
+                h: BULLET_H,
+            },
+            speed: speed,
+            moved: (0.0, 0.0),
+        }
+    }
 }
 
 impl Bullet for RectBullet {
-    fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<Bullet>> {
+    fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<dyn Bullet>> {
         let (w, _) = phi.output_size();
-        self.rect.x += BULLET_SPEED * dt;
+        self.moved = (self.speed * dt, 0.0);
+        self.rect.x += self.moved.0;
 
         // If the bullet has left the screen, then delete it.
         if self.rect.x > w { None } else { Some(self) }
@@ -40,27 +69,36 @@ impl Bullet for RectBullet {
     fn render(&self, phi: &mut Phi) {
         // We will render this kind of bullet in yellow.
         phi.renderer.set_draw_color(Color::RGB(230, 230, 30));
-        phi.renderer.fill_rect(self.rect.to_sdl().unwrap());
+        let _ = phi.renderer.fill_rect(self.rect.to_sdl().unwrap());
     }
 
     fn rect(&self) -> Rectangle {
         self.rect
     }
+
+    fn motion(&self) -> (f64, f64) {
+        self.moved
+    }
 }
 
 
 pub struct SineBullet {
     pos_x: f64,
+    speed: f64,
     origin_y: f64,
     amplitude: f64,
     angular_vel: f64,
     total_time: f64,
+    moved: (f64, f64),
 }
 
 impl Bullet for SineBullet {
-    fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<Bullet>> {
+    fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<dyn Bullet>> {
+        let before = self.rect();
         self.total_time += dt;
-        self.pos_x += BULLET_SPEED * dt;
+        self.pos_x += self.speed * dt;
+        let after = self.rect();
+        self.moved = (after.x - before.x, after.y - before.y);
 
         // If the bullet has left the screen, then delete it.
         let (w, _) = phi.output_size();
@@ -71,7 +109,7 @@ impl Bullet for SineBullet {
     fn render(&self, phi: &mut Phi) {
         // We will render this kind of bullet in yellow.
         phi.renderer.set_draw_color(Color::RGB(230, 230, 30));
-        phi.renderer.fill_rect(self.rect().to_sdl().unwrap());
+        let _ = phi.renderer.fill_rect(self.rect().to_sdl().unwrap());
     }
 
     fn rect(&self) -> Rectangle {
@@ -83,6 +121,10 @@ impl Bullet for SineBullet {
             h: BULLET_H,
         }
     }
+
+    fn motion(&self) -> (f64, f64) {
+        self.moved
+    }
 }
 
 
@@ -90,16 +132,21 @@ impl Bullet for SineBullet {
 ///     a * ((t / b)^3 - (t / b)^2)
 pub struct DivergentBullet {
     pos_x: f64,
+    speed: f64,
     origin_y: f64,
     a: f64, // Influences the bump's height
     b: f64, // Influences the bump's width
     total_time: f64,
+    moved: (f64, f64),
 }
 
 impl Bullet for DivergentBullet {
-    fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<Bullet>> {
+    fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<dyn Bullet>> {
+        let before = self.rect();
         self.total_time += dt;
-        self.pos_x += BULLET_SPEED * dt;
+        self.pos_x += self.speed * dt;
+        let after = self.rect();
+        self.moved = (after.x - before.x, after.y - before.y);
 
         // If the bullet has left the screen, then delete it.
         let (w, h) = phi.output_size();
@@ -115,7 +162,7 @@ impl Bullet for DivergentBullet {
     fn render(&self, phi: &mut Phi) {
         // We will render this kind of bullet in yellow.
         phi.renderer.set_draw_color(Color::RGB(230, 230, 30));
-        phi.renderer.fill_rect(self.rect().to_sdl().unwrap());
+        let _ = phi.renderer.fill_rect(self.rect().to_sdl().unwrap());
     }
 
     fn rect(&self) -> Rectangle {
@@ -128,6 +175,124 @@ impl Bullet for DivergentBullet {
             h: BULLET_H,
         }
     }
+
+    fn motion(&self) -> (f64, f64) {
+        self.moved
+    }
+}
+
+
+
+/// Large bullet fired by the player after charging the weapon.
+pub struct ChargeBullet {
+    rect: Rectangle,
+    /// Pixels traveled horizontally every second.
+    speed: f64,
+    moved: (f64, f64),
+}
+
+impl ChargeBullet {
+    /// Fires a bullet centered on `from`.
+    pub fn new(from: (f64, f64), speed: f64) -> ChargeBullet {
+        ChargeBullet {
+            rect: Rectangle::with_size(BULLET_W * 3.0, BULLET_H * 4.0).center_at(from),
+            speed: speed,
+            moved: (0.0, 0.0),
+        }
+    }
+}
+
+impl Bullet for ChargeBullet {
+    fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<dyn Bullet>> {
+        let (w, _) = phi.output_size();
+        self.moved = (self.speed * dt, 0.0);
+        self.rect.x += self.moved.0;
+
+        // If the bullet has left the screen, then delete it.
+        if self.rect.x > w { None } else { Some(self) }
+    }
+
+    fn render(&self, phi: &mut Phi) {
+        // A white core in a yellow glow
+        phi.renderer.set_draw_color(Color::RGB(230, 230, 30));
+        let _ = phi.renderer.fill_rect(self.rect.to_sdl().unwrap());
+        let core = Rectangle::with_size(self.rect.w - 4.0, self.rect.h - 4.0)
+            .center_at(self.rect.center());
+        phi.renderer.set_draw_color(Color::RGB(255, 255, 255));
+        let _ = phi.renderer.fill_rect(core.to_sdl().unwrap());
+    }
+
+    fn rect(&self) -> Rectangle {
+        self.rect
+    }
+
+    fn motion(&self) -> (f64, f64) {
+        self.moved
+    }
+
+    fn damage(&self) -> u32 {
+        CHARGE_DAMAGE
+    }
+}
+
+
+
+/// Bullet fired by an enemy, which travels in a straight line in any
+/// direction, typically towards the player.
+pub struct AimedBullet {
+    rect: Rectangle,
+    /// Pixels traveled every second, horizontally and vertically.
+    vel: (f64, f64),
+    moved: (f64, f64),
+}
+
+impl AimedBullet {
+    /// Fires a bullet from `from` towards `to` at `speed` pixels per second.
+    pub fn new(from: (f64, f64), to: (f64, f64), speed: f64) -> AimedBullet {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length = (dx * dx + dy * dy).sqrt();
+        let vel = if length > 0.0 {
+            (dx / length * speed, dy / length * speed)
+        } else {
+            (-speed, 0.0)
+        };
+        AimedBullet {
+            rect: Rectangle::with_size(BULLET_H * 1.5, BULLET_H * 1.5).center_at(from),
+            vel: vel,
+            moved: (0.0, 0.0),
+        }
+    }
+}
+
+impl Bullet for AimedBullet {
+    fn update(mut self: Box<Self>, phi: &mut Phi, dt: f64) -> Option<Box<dyn Bullet>> {
+        self.moved = (self.vel.0 * dt, self.vel.1 * dt);
+        self.rect.x += self.moved.0;
+        self.rect.y += self.moved.1;
+
+        // If the bullet has left the screen, then delete it.
+        let (w, h) = phi.output_size();
+        let rect = self.rect;
+        if rect.x > w || rect.x + rect.w < 0.0 || rect.y > h || rect.y + rect.h < 0.0 {
+            None
+        } else {
+            Some(self)
+        }
+    }
+
+    fn render(&self, phi: &mut Phi) {
+        // Enemy bullets are red, so that they are easy to tell apart.
+        phi.renderer.set_draw_color(Color::RGB(230, 50, 30));
+        let _ = phi.renderer.fill_rect(self.rect.to_sdl().unwrap());
+    }
+
+    fn rect(&self) -> Rectangle {
+        self.rect
+    }
+
+    fn motion(&self) -> (f64, f64) {
+        self.moved
+    }
 }
 
 
@@ -140,45 +305,38 @@ pub enum CannonType {
 }
 
 
+/// Fires both cannons. `speed` is the number of pixels traveled horizontally by
+/// the bullets every second.
 pub fn spawn_bullets(cannon: CannonType,
+                     speed: f64,
                      cannons_x: f64,
                      cannon1_y: f64,
                      cannon2_y: f64)
-                     -> Vec<Box<Bullet>> {
+                     -> Vec<Box<dyn Bullet>> {
     match cannon {
         CannonType::RectBullet => {
-            vec![Box::new(RectBullet {
-                              rect: Rectangle {
-                                  x: cannons_x,
-                                  y: cannon1_y,
-                                  w: BULLET_W,
-                                  h: BULLET_H,
-                              },
-                          }),
-                 Box::new(RectBullet {
-                              rect: Rectangle {
-                                  x: cannons_x,
-                                  y: cannon2_y,
-                                  w: BULLET_W,
-                                  h: BULLET_H,
-                              },
-                          })]
+            vec![Box::new(RectBullet::new(cannons_x, cannon1_y, speed)),
+                 Box::new(RectBullet::new(cannons_x, cannon2_y, speed))]
         }
 
         CannonType::SineBullet { amplitude, angular_vel } => {
             vec![Box::new(SineBullet {
                               pos_x: cannons_x,
+                              speed: speed,
                               origin_y: cannon1_y,
                               amplitude: amplitude,
                               angular_vel: angular_vel,
                               total_time: 0.0,
+                              moved: (0.0, 0.0),
                           }),
                  Box::new(SineBullet {
                               pos_x: cannons_x,
+                              speed: speed,
                               origin_y: cannon2_y,
                               amplitude: amplitude,
                               angular_vel: angular_vel,
                               total_time: 0.0,
+                              moved: (0.0, 0.0),
                           })]
         }
 
@@ -186,18 +344,22 @@ pub fn spawn_bullets(cannon: CannonType,
             vec![// If a,b > 0, eventually goes upwards
                  Box::new(DivergentBullet {
                               pos_x: cannons_x,
+                              speed: speed,
                               origin_y: cannon1_y,
                               a: -a,
                               b: b,
                               total_time: 0.0,
+                              moved: (0.0, 0.0),
                           }),
                  // If a,b > 0, eventually goes downwards
                  Box::new(DivergentBullet {
                               pos_x: cannons_x,
+                              speed: speed,
                               origin_y: cannon2_y,
                               a: a,
                               b: b,
                               total_time: 0.0,
+                              moved: (0.0, 0.0),
                           })]
         }
     }
@@ -0,0 +1,132 @@
+use phi::{Bindings, Input, Phi, View, ViewAction};
+use sdl2::keyboard::Keycode;
+use views::menu::{dim_screen, Action, Menu};
+use views::shared::BgSet;
+
+const FONT_SIZE: i32 = 20;
+const LABEL_H: f64 = 32.0;
+
+/// Lets the player change the inputs bound to every logical key. Choosing a
+/// logical key waits for a key or a controller button to be pressed, which is
+/// then added to its bindings, or removed from them if it was already there.
+/// The Escape key cancels the wait instead, and can therefore not be bound.
+pub struct ControlsView {
+    menu: Menu,
+    /// The logical keys, in the same order as the first actions of the menu.
+    names: Vec<&'static str>,
+    /// Index of the logical key waiting for a physical input, if any.
+    waiting: Option<usize>,
+    bg: BgSet,
+}
+
+impl ControlsView {
+    pub fn new(phi: &mut Phi, bg: BgSet) -> ControlsView {
+        let names = Bindings::names();
+        let mut actions: Vec<Action> = names.iter()
+            .map(|name| {
+                let label = binding_label(phi, name);
+                Action::with_font_size(phi, &label, FONT_SIZE, Box::new(|_, _| ViewAction::None))
+            })
+            .collect();
+        actions.push(Action::with_font_size(phi,
+                                            "Reset Defaults",
+                                            FONT_SIZE,
+                                            Box::new(|phi, _| {
+                                                         phi.events.bindings = Bindings::default();
+                                                         ViewAction::None
+                                                     })));
+        actions.push(Action::with_font_size(phi, "Back", FONT_SIZE, Box::new(|_, _| ViewAction::Pop)));
+
+        ControlsView {
+            menu: Menu::with_label_height(actions, LABEL_H),
+            names: names,
+            waiting: None,
+            bg: bg,
+        }
+    }
+
+    fn refresh_labels(&mut self, phi: &mut Phi) {
+        for (i, name) in self.names.iter().enumerate() {
+            let label = binding_label(phi, name);
+            self.menu.actions[i].set_label(phi, &label);
+        }
+    }
+}
+
+impl View for ControlsView {
+    fn exit(&mut self, phi: &mut Phi) {
+        if let Err(e) = phi.events.bindings.save() {
+            println!("Could not save the controls: {}", e);
+        }
+    }
+
+    fn update(&mut self, phi: &mut Phi, _elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        if let Some(i) = self.waiting {
+            if phi.events.now.pressed == Some(Input::Key(Keycode::Escape)) {
+                self.waiting = None;
+                self.refresh_labels(phi);
+            } else if let Some(input) = phi.events.now.pressed {
+                {
+                    let inputs = phi.events.bindings.get_mut(self.names[i]).unwrap();
+                    match inputs.iter().position(|&i| i == input) {
+                        // Never leave a logical key without any binding
+                        Some(pos) => {
+                            if inputs.len() > 1 {
+                                inputs.remove(pos);
+                            }
+                        }
+                        None => inputs.push(input),
+                    }
+                }
+                self.waiting = None;
+                self.refresh_labels(phi);
+            }
+            return ViewAction::None;
+        }
+
+        if phi.events.now.key_escape == Some(true) {
+            return ViewAction::Pop;
+        }
+
+        match self.menu.update(phi, &self.bg) {
+            Some((i, _)) if i < self.names.len() => {
+                let label = format!("{}: press a key or button", display_name(self.names[i]));
+                self.menu.actions[i].set_label(phi, &label);
+                self.waiting = Some(i);
+                ViewAction::None
+            }
+            Some((_, action)) => {
+                self.refresh_labels(phi);
+                action
+            }
+            None => ViewAction::None,
+        }
+    }
+
+    fn draw(&self, phi: &mut Phi, _alpha: f64) {
+        dim_screen(phi);
+        self.menu.render(phi);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
+
+/// Turns `key_space` into `Space`.
+fn display_name(name: &str) -> String {
+    let name = name.trim_start_matches("key_");
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn binding_label(phi: &Phi, name: &str) -> String {
+    format!("{}: {}", display_name(name), phi.events.bindings.describe(name))
+}
@@ -0,0 +1,92 @@
+use phi::{Phi, View, ViewAction};
+use phi::gfx::{CopySprite, Sprite};
+use phi::data::Rectangle;
+use sdl2::pixels::Color;
+use views::game::GameView;
+use views::shared::BgSet;
+
+/// The game played after starting with `--record-demo` is recorded to this
+/// file in the user's data directory, so that the main menu can play it back.
+/// Other games leave it alone.
+pub const DEMO_FILE: &str = "demo.rec";
+
+/// Plays back the recorded demo, arcade style, until it is over or the player
+/// presses anything.
+pub struct DemoView {
+    game: GameView,
+    label: Sprite,
+    /// Whether the recording could not be loaded, in which case the demo
+    /// ends right away.
+    failed: bool,
+}
+
+impl DemoView {
+    pub fn new(phi: &mut Phi, bg: BgSet) -> DemoView {
+        DemoView {
+            game: GameView::with_backgrounds(phi, bg),
+            label: phi.ttf_str_sprite("Demo - Press any key",
+                                 "assets/belligerent.ttf",
+                                 24,
+                                 Color::RGB(255, 255, 255))
+                .unwrap(),
+            failed: false,
+        }
+    }
+
+    /// Whether there is a recorded game to show.
+    pub fn is_available() -> bool {
+        ::phi::user_file(DEMO_FILE).is_some_and(|path| path.exists())
+    }
+}
+
+impl View for DemoView {
+    fn enter(&mut self, phi: &mut Phi) {
+        // The replay must be started before the game, which would otherwise
+        // record itself over the demo.
+        match ::phi::user_file(DEMO_FILE).ok_or("no user data directory".to_string())
+            .and_then(|path| phi.start_replay(&path)) {
+            Ok(()) => self.game.enter(phi),
+            Err(e) => {
+                println!("Could not play the demo: {}", e);
+                self.failed = true;
+            }
+        }
+    }
+
+    fn exit(&mut self, phi: &mut Phi) {
+        if !self.failed {
+            self.game.exit(phi);
+        }
+        phi.events.stop_replay();
+    }
+
+    fn update(&mut self, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+        if self.failed || phi.events.now.interrupted || !phi.events.is_replaying() {
+            return ViewAction::Pop;
+        }
+
+        // Whenever the recorded game would have left, e.g. to pause or
+        // because it is over, go back to the menu instead.
+        match self.game.update(phi, elapsed) {
+            ViewAction::None => ViewAction::None,
+            _ => ViewAction::Pop,
+        }
+    }
+
+    fn draw(&self, phi: &mut Phi, alpha: f64) {
+        self.game.draw(phi, alpha);
+
+        let (win_w, win_h) = phi.output_size();
+        let (w, h) = self.label.size();
+        phi.renderer.copy_sprite(&self.label,
+                                 Rectangle {
+                                     x: (win_w - w) / 2.0,
+                                     y: win_h - h - 20.0,
+                                     w: w,
+                                     h: h,
+                                 });
+    }
+}
@@ -0,0 +1,346 @@
+use rand::Rng;
+
+/// A value which moves linearly from `from` to `to` over the course of a
+/// wave.
+#[derive(Clone, Copy, Debug)]
+pub struct Ramp {
+    pub from: f64,
+    pub to: f64,
+}
+
+impl Ramp {
+    pub fn constant(value: f64) -> Ramp {
+        Ramp {
+            from: value,
+            to: value,
+        }
+    }
+
+    /// The value once `progress`, in [0, 1], of the wave has elapsed.
+    pub fn at(&self, progress: f64) -> f64 {
+        self.from + (self.to - self.from) * progress
+    }
+}
+
+/// The kinds of objects a level may throw at the player.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EnemyKind {
+    Asteroid,
+    /// A ship which flies straight.
+    Fighter,
+    /// A ship which weaves up and down.
+    Weaver,
+    /// A ship which dives towards the player.
+    Swooper,
+    /// A ship which follows the player.
+    Hunter,
+}
+
+impl EnemyKind {
+    pub fn from_name(name: &str) -> Option<EnemyKind> {
+        match name {
+            "asteroid" => Some(EnemyKind::Asteroid),
+            "fighter" => Some(EnemyKind::Fighter),
+            "weaver" => Some(EnemyKind::Weaver),
+            "swooper" => Some(EnemyKind::Swooper),
+            "hunter" => Some(EnemyKind::Hunter),
+            _ => None,
+        }
+    }
+}
+
+/// A stretch of the game during which asteroids spawn at a given pace.
+#[derive(Clone, Debug)]
+pub struct Wave {
+    /// Seconds the wave lasts. Once the last wave is over, the game keeps
+    /// going as it ended.
+    pub duration: f64,
+    pub enemy: EnemyKind,
+    /// Asteroids spawned per second, on average.
+    pub rate: Ramp,
+    /// Slowest and fastest speed of the asteroids, in pixels per second.
+    pub min_speed: Ramp,
+    pub max_speed: Ramp,
+    /// Size of the asteroids, relative to their sprite.
+    pub scale: Ramp,
+}
+
+/// The waves of a game which does not say otherwise: a calm start, then
+/// asteroids which keep getting more numerous, faster and bigger.
+pub fn default_waves() -> Vec<Wave> {
+    vec![Wave {
+             duration: 20.0,
+             enemy: EnemyKind::Asteroid,
+             rate: Ramp { from: 0.4, to: 0.8 },
+             min_speed: Ramp::constant(50.0),
+             max_speed: Ramp { from: 120.0, to: 150.0 },
+             scale: Ramp::constant(1.0),
+         },
+         Wave {
+             duration: 40.0,
+             enemy: EnemyKind::Asteroid,
+             rate: Ramp { from: 0.8, to: 1.5 },
+             min_speed: Ramp { from: 60.0, to: 90.0 },
+             max_speed: Ramp { from: 150.0, to: 220.0 },
+             scale: Ramp { from: 1.0, to: 1.2 },
+         },
+         Wave {
+             duration: 60.0,
+             enemy: EnemyKind::Asteroid,
+             rate: Ramp { from: 1.5, to: 2.5 },
+             min_speed: Ramp { from: 90.0, to: 120.0 },
+             max_speed: Ramp { from: 220.0, to: 300.0 },
+             scale: Ramp { from: 1.2, to: 1.4 },
+         }]
+}
+
+impl Default for Wave {
+    fn default() -> Wave {
+        Wave {
+            duration: 30.0,
+            enemy: EnemyKind::Asteroid,
+            rate: Ramp::constant(1.0),
+            min_speed: Ramp::constant(50.0),
+            max_speed: Ramp::constant(150.0),
+            scale: Ramp::constant(1.0),
+        }
+    }
+}
+
+/// What the director asks for an enemy to look like.
+#[derive(Clone, Debug)]
+pub struct Spawn {
+    pub enemy: EnemyKind,
+    /// In pixels per second.
+    pub speed: f64,
+    /// Relative to the size of the enemy's sprite.
+    pub scale: f64,
+    /// Where the enemy appears, as a fraction of the height of the screen,
+    /// or `None` to pick a random position.
+    pub y: Option<f64>,
+}
+
+/// An enemy which appears at a given time, regardless of the waves.
+#[derive(Clone, Debug)]
+pub struct ScheduledSpawn {
+    /// Seconds since the start of the game.
+    pub time: f64,
+    pub spawn: Spawn,
+}
+
+/// Decides when enemies should appear, following a list of waves and of
+/// scheduled spawns. Only the time elapsed in the game matters, not the frame
+/// rate.
+pub struct SpawnDirector {
+    waves: Vec<Wave>,
+    /// Scheduled spawns which have yet to happen, latest first.
+    scheduled: Vec<ScheduledSpawn>,
+    /// Seconds since the game started.
+    time: f64,
+    /// Index of the current wave.
+    current: usize,
+    /// Seconds since the current wave started.
+    wave_time: f64,
+    /// Seconds until the next asteroid.
+    until_next: f64,
+}
+
+impl SpawnDirector {
+    pub fn new(waves: Vec<Wave>, mut scheduled: Vec<ScheduledSpawn>) -> SpawnDirector {
+        assert!(!waves.is_empty(), "a game needs at least one wave");
+        scheduled.sort_by(|a, b| b.time.partial_cmp(&a.time).unwrap());
+        SpawnDirector {
+            waves: waves,
+            scheduled: scheduled,
+            time: 0.0,
+            current: 0,
+            wave_time: 0.0,
+            // Give the player a moment before the first asteroid
+            until_next: 1.0,
+        }
+    }
+
+    /// Advances the schedule by `dt` seconds, and returns the enemies which
+    /// should appear meanwhile. Their speeds and intervals are drawn from
+    /// `rng`, usually the gameplay randomness of `Phi`.
+    pub fn update<R: Rng>(&mut self, rng: &mut R, dt: f64) -> Vec<Spawn> {
+        let mut spawns = vec![];
+        self.time += dt;
+        while self.scheduled.last().is_some_and(|s| s.time <= self.time) {
+            spawns.push(self.scheduled.pop().unwrap().spawn);
+        }
+
+        self.wave_time += dt;
+        while self.current + 1 < self.waves.len() &&
+              self.wave_time >= self.waves[self.current].duration {
+            self.wave_time -= self.waves[self.current].duration;
+            self.current += 1;
+        }
+
+        let wave = &self.waves[self.current];
+        let progress = (self.wave_time / wave.duration).min(1.0);
+        self.until_next -= dt;
+        while self.until_next <= 0.0 {
+            let (min_speed, max_speed) = (wave.min_speed.at(progress), wave.max_speed.at(progress));
+            spawns.push(Spawn {
+                enemy: wave.enemy,
+                speed: if max_speed > min_speed {
+                    rng.gen_range(min_speed, max_speed)
+                } else {
+                    min_speed
+                },
+                scale: wave.scale.at(progress),
+                y: None,
+            });
+
+            // Irregular intervals, which average out to the wave's rate
+            let rate = wave.rate.at(progress).max(0.01);
+            self.until_next += rng.gen_range(0.5, 1.5) / rate;
+        }
+        spawns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, XorShiftRng};
+
+    const DT: f64 = 1.0 / 60.0;
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
+    fn wave(duration: f64, enemy: EnemyKind, rate: f64) -> Wave {
+        Wave {
+            duration: duration,
+            enemy: enemy,
+            rate: Ramp::constant(rate),
+            ..Wave::default()
+        }
+    }
+
+    fn scheduled(time: f64, enemy: EnemyKind) -> ScheduledSpawn {
+        ScheduledSpawn {
+            time: time,
+            spawn: Spawn {
+                enemy: enemy,
+                speed: 100.0,
+                scale: 1.0,
+                y: Some(0.5),
+            },
+        }
+    }
+
+    /// Runs the director for `seconds`, and returns everything it spawned.
+    fn run(director: &mut SpawnDirector, rng: &mut XorShiftRng, seconds: f64) -> Vec<Spawn> {
+        let mut spawns = vec![];
+        for _ in 0..(seconds / DT).round() as u32 {
+            spawns.extend(director.update(rng, DT));
+        }
+        spawns
+    }
+
+    #[test]
+    fn ramps_go_from_one_bound_to_the_other() {
+        let ramp = Ramp { from: 2.0, to: 4.0 };
+        assert_eq!((ramp.at(0.0), ramp.at(0.5), ramp.at(1.0)), (2.0, 3.0, 4.0));
+        assert_eq!(Ramp::constant(5.0).at(0.3), 5.0);
+    }
+
+    #[test]
+    fn scheduled_spawns_happen_in_order_at_their_time() {
+        let mut rng = rng();
+        let mut director = SpawnDirector::new(vec![wave(10.0, EnemyKind::Asteroid, 0.01)],
+                                              vec![scheduled(2.0, EnemyKind::Hunter),
+                                                   scheduled(1.5, EnemyKind::Fighter),
+                                                   scheduled(2.0, EnemyKind::Weaver)]);
+        assert!(director.update(&mut rng, 0.5).is_empty());
+        // All three are due by the end of a single long step
+        let scheduled: Vec<Spawn> = director.update(&mut rng, 1.5)
+            .into_iter()
+            .filter(|s| s.enemy != EnemyKind::Asteroid)
+            .collect();
+        assert_eq!(scheduled.len(), 3);
+        assert_eq!(scheduled[0].enemy, EnemyKind::Fighter);
+        assert!(scheduled.iter().all(|s| s.y == Some(0.5)));
+        assert!(director.update(&mut rng, 60.0).iter().all(|s| s.enemy == EnemyKind::Asteroid));
+    }
+
+    #[test]
+    fn the_first_asteroid_waits_a_moment() {
+        let mut rng = rng();
+        let mut director = SpawnDirector::new(vec![wave(10.0, EnemyKind::Asteroid, 100.0)], vec![]);
+        assert!(run(&mut director, &mut rng, 0.9).is_empty());
+        assert!(!run(&mut director, &mut rng, 0.2).is_empty());
+    }
+
+    #[test]
+    fn asteroids_spawn_at_the_rate_of_the_wave() {
+        let mut rng = rng();
+        let mut director = SpawnDirector::new(vec![wave(10.0, EnemyKind::Asteroid, 2.0)], vec![]);
+        let count = run(&mut director, &mut rng, 1000.0).len() as f64;
+        assert!(count > 1900.0 && count < 2100.0, "{} spawns", count);
+    }
+
+    #[test]
+    fn the_rate_does_not_depend_on_the_frame_rate() {
+        let (mut slow, mut fast) = (rng(), rng());
+        let waves = vec![wave(10.0, EnemyKind::Asteroid, 3.0)];
+        let mut director = SpawnDirector::new(waves.clone(), vec![]);
+        let mut count = 0;
+        for _ in 0..100 {
+            count += director.update(&mut slow, 1.0).len();
+        }
+        // The same random numbers are drawn, in the same order
+        assert_eq!(count, run(&mut SpawnDirector::new(waves, vec![]), &mut fast, 100.0).len());
+    }
+
+    #[test]
+    fn waves_follow_each_other_and_the_last_one_lasts() {
+        let mut rng = rng();
+        let mut director = SpawnDirector::new(vec![wave(10.0, EnemyKind::Fighter, 5.0),
+                                                   wave(10.0, EnemyKind::Weaver, 5.0)],
+                                              vec![]);
+        assert!(run(&mut director, &mut rng, 9.5).iter().all(|s| s.enemy == EnemyKind::Fighter));
+        run(&mut director, &mut rng, 1.0);
+        let spawns = run(&mut director, &mut rng, 100.0);
+        assert!(!spawns.is_empty());
+        assert!(spawns.iter().all(|s| s.enemy == EnemyKind::Weaver));
+    }
+
+    #[test]
+    fn speeds_and_scales_follow_the_wave() {
+        let mut rng = rng();
+        let mut director = SpawnDirector::new(vec![Wave {
+                                                       duration: 10.0,
+                                                       enemy: EnemyKind::Asteroid,
+                                                       rate: Ramp::constant(10.0),
+                                                       min_speed: Ramp::constant(50.0),
+                                                       max_speed: Ramp { from: 60.0, to: 100.0 },
+                                                       scale: Ramp { from: 1.0, to: 2.0 },
+                                                   }],
+                                              vec![]);
+        let spawns = run(&mut director, &mut rng, 10.0);
+        assert!(spawns.iter().all(|s| s.speed >= 50.0 && s.speed < 100.0));
+        assert!(spawns.iter().all(|s| s.scale >= 1.0 && s.scale <= 2.0 && s.y.is_none()));
+        assert!(spawns.first().unwrap().scale < 1.2);
+        assert!(spawns.last().unwrap().scale > 1.8);
+
+        // A wave whose speeds cross uses the slowest one
+        let mut director = SpawnDirector::new(vec![Wave {
+                                                       min_speed: Ramp::constant(80.0),
+                                                       max_speed: Ramp::constant(40.0),
+                                                       ..wave(10.0, EnemyKind::Asteroid, 10.0)
+                                                   }],
+                                              vec![]);
+        assert!(run(&mut director, &mut rng, 5.0).iter().all(|s| s.speed == 80.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn a_game_needs_waves() {
+        SpawnDirector::new(vec![], vec![]);
+    }
+}
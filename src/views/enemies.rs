@@ -0,0 +1,174 @@
+use phi::Phi;
+use phi::data::{Rectangle, Shape};
+use phi::gfx::{CopySprite, Sprite};
+use rand::Rng;
+use sdl2::pixels::Color;
+use std::f64::consts::PI;
+use views::bullets::{AimedBullet, Bullet};
+use views::director::{EnemyKind, Spawn};
+
+const ENEMY_PATH: &str = "assets/enemy.png";
+const ENEMY_W: f64 = 43.0;
+const ENEMY_H: f64 = 39.0;
+/// Pixels traveled by an enemy bullet every second.
+const ENEMY_BULLET_SPEED: f64 = 200.0;
+/// Range of seconds between two shots of the same enemy.
+const FIRE_INTERVAL: (f64, f64) = (1.5, 3.0);
+/// How far a weaver moves up and down, in pixels, and how many times per
+/// second.
+const SINE_AMPLITUDE: f64 = 60.0;
+const SINE_FREQUENCY: f64 = 0.5;
+/// How fast a hunter moves vertically, relative to its horizontal speed.
+const FOLLOW_RATIO: f64 = 0.6;
+/// The convex hull of the solid pixels of the sprite, in its own pixels,
+/// going clockwise from the tip of the nose.
+const OUTLINE: [(f64, f64); 14] = [(0.0, 18.0), (2.0, 16.0), (14.0, 6.0), (27.0, 0.0), (30.0, 0.0),
+                                   (40.0, 5.0), (43.0, 8.0), (43.0, 31.0), (40.0, 34.0), (30.0, 39.0),
+                                   (27.0, 39.0), (14.0, 33.0), (2.0, 23.0), (0.0, 21.0)];
+const DEBUG: bool = false;
+
+/// The way an enemy moves across the screen.
+#[derive(Clone, Copy, Debug)]
+pub enum Pattern {
+    /// Right to left, without ever changing height.
+    Straight,
+    /// Right to left, weaving up and down around its initial height.
+    Sine,
+    /// Dives towards where the player was when it appeared, then climbs back
+    /// to its initial height.
+    Swoop { target_y: f64 },
+    /// Keeps adjusting its height to the player's.
+    Follow,
+}
+
+/// A hostile ship, which moves according to its pattern and shoots at the
+/// player.
+pub struct Enemy {
+    rect: Rectangle,
+    pattern: Pattern,
+    /// Pixels traveled horizontally every second.
+    speed: f64,
+    /// Position at which the enemy appeared.
+    origin: (f64, f64),
+    /// Seconds since the enemy appeared.
+    time: f64,
+    /// Seconds until the next shot.
+    fire_in: f64,
+    sprite: Sprite,
+}
+
+impl Enemy {
+    /// Moves the enemy, and fires at `target`, the center of the player's
+    /// ship if it is alive. Returns `None` once the enemy has left the screen.
+    pub fn update(mut self,
+                  phi: &mut Phi,
+                  dt: f64,
+                  target: Option<(f64, f64)>,
+                  bullets: &mut Vec<Box<dyn Bullet>>)
+                  -> Option<Enemy> {
+        self.time += dt;
+        self.rect.x -= self.speed * dt;
+
+        let (w, h) = phi.output_size();
+        match self.pattern {
+            Pattern::Straight => {}
+            Pattern::Sine => {
+                self.rect.y = self.origin.1 + SINE_AMPLITUDE * (2.0 * PI * SINE_FREQUENCY * self.time).sin();
+            }
+            Pattern::Swoop { target_y } => {
+                // Halfway through the screen, the enemy is at its lowest
+                let progress = ((self.origin.0 - self.rect.x) / w).clamp(0.0, 1.0);
+                self.rect.y = self.origin.1 + (target_y - self.origin.1) * (PI * progress).sin();
+            }
+            Pattern::Follow => {
+                if let Some((_, target_y)) = target {
+                    let dy = target_y - self.rect.center().1;
+                    let max_dy = self.speed * FOLLOW_RATIO * dt;
+                    self.rect.y += dy.max(-max_dy).min(max_dy);
+                }
+            }
+        }
+        self.rect.y = self.rect.y.max(0.0).min(h - self.rect.h);
+
+        // Only shoot forward, once on the screen
+        self.fire_in -= dt;
+        if self.fire_in <= 0.0 {
+            self.fire_in = phi.rng.gen_range(FIRE_INTERVAL.0, FIRE_INTERVAL.1);
+            if let Some(target) = target {
+                let from = self.rect.center();
+                if self.rect.x + self.rect.w < w && from.0 > target.0 {
+                    bullets.push(Box::new(AimedBullet::new(from, target, ENEMY_BULLET_SPEED)));
+                }
+            }
+        }
+
+        if self.rect.x <= -self.rect.w {
+            None
+        } else {
+            Some(self)
+        }
+    }
+
+    pub fn render(&self, phi: &mut Phi) {
+        if DEBUG {
+            // Render the bounding box
+            phi.renderer.set_draw_color(Color::RGB(200, 50, 50));
+            let _ = phi.renderer.fill_rect(self.rect.to_sdl().unwrap());
+        }
+        phi.renderer.copy_sprite(&self.sprite, self.rect);
+    }
+
+    pub fn rect(&self) -> Rectangle {
+        self.rect
+    }
+
+    /// The outline of the sprite, stretched over the enemy.
+    pub fn shape(&self) -> Shape {
+        let r = self.rect;
+        let (sx, sy) = (r.w / ENEMY_W, r.h / ENEMY_H);
+        Shape::polygon(OUTLINE.iter().map(|&(x, y)| (r.x + x * sx, r.y + y * sy)).collect())
+    }
+}
+
+pub struct EnemyFactory {
+    sprite: Sprite,
+}
+
+impl EnemyFactory {
+    pub fn new(phi: &mut Phi) -> EnemyFactory {
+        EnemyFactory { sprite: Sprite::load(&phi.renderer, ENEMY_PATH).unwrap() }
+    }
+
+    /// Creates an enemy right of the screen. `target` is the center of the
+    /// player's ship, which swooping enemies dive towards.
+    pub fn spawn(&self, phi: &mut Phi, spawn: &Spawn, target: (f64, f64)) -> Enemy {
+        let (w, h) = phi.output_size();
+        let (enemy_w, enemy_h) = (ENEMY_W * spawn.scale, ENEMY_H * spawn.scale);
+        let y = match spawn.y {
+            Some(y) => y * (h - enemy_h),
+            None => phi.rng.gen_range(0.0, (h - enemy_h).max(1.0)),
+        };
+        let pattern = match spawn.enemy {
+            EnemyKind::Weaver => Pattern::Sine,
+            EnemyKind::Swooper => Pattern::Swoop { target_y: target.1 - enemy_h / 2.0 },
+            EnemyKind::Hunter => Pattern::Follow,
+            EnemyKind::Fighter | EnemyKind::Asteroid => Pattern::Straight,
+        };
+
+        Enemy {
+            rect: Rectangle {
+                x: w,
+                y: y,
+                w: enemy_w,
+                h: enemy_h,
+            },
+            pattern: pattern,
+            speed: spawn.speed,
+            origin: (w, y),
+            time: 0.0,
+            // Let the player see the enemy before it shoots
+            fire_in: phi.rng.gen_range(FIRE_INTERVAL.0, FIRE_INTERVAL.1) / 2.0,
+            sprite: self.sprite.clone(),
+        }
+    }
+}
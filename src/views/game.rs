@@ -1,39 +1,121 @@
 use phi::{Phi, View, ViewAction};
+use rand::Rng;
 use phi::gfx::{CopySprite, Sprite, AnimatedSprite, AnimatedSpriteDescr};
-use phi::data::{Rectangle, MaybeAlive};
+use phi::data::{Grid, Mask, MaybeAlive, Rectangle, Shape};
 use sdl2::pixels::Color;
 use sdl2::mixer::{Chunk, Music};
 use std::path::Path;
+use std::rc::Rc;
+use views::boss::{Boss, BossDescr, Hit};
 use views::bullets::*;
+use views::demo::DEMO_FILE;
+use views::director::{EnemyKind, Spawn, SpawnDirector};
+use views::enemies::{Enemy, EnemyFactory};
+use views::high_scores::HighScores;
+use views::hud::Hud;
+use views::level::{DEFAULT_LEVEL, Level, Tuning};
+use views::name_entry::NameEntryView;
+use views::physics::Physics;
+use views::powerups::{BOOST_DURATION, BOOST_FACTOR, PowerUp, PowerUpFactory, PowerUpKind,
+                      SHIELD_DURATION};
+use views::score::Score;
 use views::shared::BgSet;
+use views::weapons::Weapon;
 
 // Constants
-const PLAYER_SPEED: f64 = 180.0;
-const PLAYER_PATH: &'static str = "assets/spaceship.png";
+const PLAYER_PATH: &str = "assets/spaceship.png";
 const PLAYER_W: f64 = 43.0;
 const PLAYER_H: f64 = 39.0;
 const DEBUG: bool = false;
-const ASTEROID_PATH: &'static str = "assets/asteroid.png";
+const ASTEROID_PATH: &str = "assets/asteroid.png";
 const ASTEROIDS_WIDE: usize = 21;
 const ASTEROIDS_HIGH: usize = 7;
 const ASTEROIDS_TOTAL: usize = ASTEROIDS_WIDE * ASTEROIDS_HIGH - 4;
+/// Size of a frame of the asteroid's spritesheet.
 const ASTEROID_SIDE: f64 = 96.0;
-const EXPLOSION_PATH: &'static str = "assets/explosion.png";
+const EXPLOSION_PATH: &str = "assets/explosion.png";
 const EXPLOSIONS_WIDE: usize = 5;
 const EXPLOSIONS_HIGH: usize = 4;
 const EXPLOSIONS_TOTAL: usize = 17;
 const EXPLOSION_SIDE: f64 = 96.0;
 const EXPLOSION_FPS: f64 = 16.0;
 const EXPLOSION_DURATION: f64 = 1.0 / EXPLOSION_FPS * EXPLOSIONS_TOTAL as f64;
+/// Side of the cells of the grid which finds the bullets near each
+/// asteroid, about the size of an asteroid.
+const BROAD_PHASE_CELL: f64 = 128.0;
+/// Radius of the round part of an asteroid's sprite, relative to its side.
+const ASTEROID_RADIUS: f64 = 0.4;
+/// Pixels of the ship more opaque than this can be hit.
+const ALPHA_THRESHOLD: u8 = 64;
+/// Fastest rotation of a new asteroid, in degrees per second.
+const MAX_SPIN: f64 = 90.0;
+/// Vertical speed, in pixels per second, at which fragments move away from
+/// each other when an asteroid splits.
+const FRAGMENT_SPREAD: (f64, f64) = (40.0, 100.0);
+/// Fragments fly faster than the asteroid they came from, by this factor.
+const FRAGMENT_SPEEDUP: f64 = 1.2;
+/// Seconds between the destruction of the boss and the end of the game.
+const LEVEL_CLEAR_DELAY: f64 = 4.0;
+/// Times per second a ship blinks while it is invulnerable.
+const BLINK_FREQUENCY: f64 = 10.0;
+
+/// Asteroids come in three sizes. Larger ones take more hits, and split into
+/// smaller ones when destroyed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum AsteroidSize {
+    Large,
+    Medium,
+    Small,
+}
+
+impl AsteroidSize {
+    /// Relative to a large asteroid.
+    fn scale(&self) -> f64 {
+        match *self {
+            AsteroidSize::Large => 1.0,
+            AsteroidSize::Medium => 0.6,
+            AsteroidSize::Small => 0.35,
+        }
+    }
+
+    fn hit_points(&self) -> u32 {
+        match *self {
+            AsteroidSize::Large => 3,
+            AsteroidSize::Medium => 2,
+            AsteroidSize::Small => 1,
+        }
+    }
+
+    /// The size of the fragments an asteroid splits into, if any.
+    fn smaller(&self) -> Option<AsteroidSize> {
+        match *self {
+            AsteroidSize::Large => Some(AsteroidSize::Medium),
+            AsteroidSize::Medium => Some(AsteroidSize::Small),
+            AsteroidSize::Small => None,
+        }
+    }
+}
 
 struct Asteroid {
     sprite: AnimatedSprite,
     rect: Rectangle,
-    vel: f64,
+    /// Pixels traveled every second, horizontally and vertically.
+    vel: (f64, f64),
+    /// Rotation, in degrees clockwise, and how fast it changes, in degrees
+    /// per second.
+    angle: f64,
+    spin: f64,
+    size: AsteroidSize,
+    /// Hits left before the asteroid is destroyed.
+    hit_points: u32,
+    /// Side of a large asteroid of the same wave, in pixels.
+    large_side: f64,
+    /// Seconds since the asteroid, or the one it split from, appeared.
+    age: f64,
 }
 
 impl Asteroid {
-    fn factory(phi: &mut Phi) -> AsteroidFactory {
+    fn factory(phi: &mut Phi, side: f64) -> AsteroidFactory {
         AsteroidFactory {
             sprite: AnimatedSprite::with_fps(AnimatedSprite::load_frames(phi,
                                                                          AnimatedSpriteDescr {
@@ -49,43 +131,99 @@ impl Asteroid {
                                                                              frame_h: ASTEROID_SIDE,
                                                                          }),
                                              1.0),
+            side: side,
         }
     }
 
-    fn update(mut self, dt: f64) -> Option<Asteroid> {
-        self.rect.x -= dt * self.vel;
+    fn update(mut self, physics: &Physics, screen: (f64, f64), dt: f64) -> Option<Asteroid> {
+        self.angle = (self.angle + self.spin * dt) % 360.0;
         self.sprite.add_time(dt);
+        self.age += dt;
 
-        if self.rect.x <= -ASTEROID_SIDE {
-            None
-        } else {
+        if physics.step(screen, &mut self.rect, &mut self.vel, self.age, dt) {
             Some(self)
+        } else {
+            None
         }
     }
 
+    /// Asteroids are round, whatever their rotation.
+    fn shape(&self) -> Shape {
+        Shape::Circle {
+            center: self.rect.center(),
+            radius: self.rect.w * ASTEROID_RADIUS,
+        }
+    }
+
+    /// Pixels traveled every second.
+    fn speed(&self) -> f64 {
+        (self.vel.0 * self.vel.0 + self.vel.1 * self.vel.1).sqrt()
+    }
+
     fn render(&self, phi: &mut Phi) {
         if DEBUG {
             // Render the bounding box
             phi.renderer.set_draw_color(Color::RGB(200, 200, 50));
-            phi.renderer.fill_rect(self.rect().to_sdl().unwrap());
+            let _ = phi.renderer.fill_rect(self.rect().to_sdl().unwrap());
         }
-        phi.renderer.copy_sprite(&self.sprite, self.rect);
+        self.sprite.render_ex(&mut phi.renderer, self.rect, self.angle, false);
     }
     fn rect(&self) -> Rectangle {
 
         self.rect
     }
+
+    /// Takes a hit which deals `damage` hit points. Returns whether the
+    /// asteroid was destroyed.
+    fn hit(&mut self, damage: u32) -> bool {
+        self.hit_points = self.hit_points.saturating_sub(damage);
+        self.hit_points == 0
+    }
+
+    /// The fragments left behind once the asteroid is destroyed, which fly
+    /// apart from each other.
+    fn split(&self, phi: &mut Phi) -> Vec<Asteroid> {
+        let size = match self.size.smaller() {
+            Some(size) => size,
+            None => return vec![],
+        };
+        let side = self.large_side * size.scale();
+        [-1.0, 1.0]
+            .iter()
+            .map(|&direction| {
+                let spread = phi.rng.gen_range(FRAGMENT_SPREAD.0, FRAGMENT_SPREAD.1);
+                Asteroid {
+                    sprite: self.sprite.clone(),
+                    rect: Rectangle::with_size(side, side).center_at(self.rect.center()),
+                    vel: (self.vel.0 * FRAGMENT_SPEEDUP, self.vel.1 + direction * spread),
+                    angle: self.angle,
+                    spin: self.spin + direction * phi.rng.gen_range(0.0, MAX_SPIN),
+                    size: size,
+                    hit_points: size.hit_points(),
+                    large_side: self.large_side,
+                    age: self.age,
+                }
+            })
+            .collect()
+    }
 }
 struct AsteroidFactory {
     sprite: AnimatedSprite,
+    /// Size of an asteroid of scale 1, in pixels.
+    side: f64,
 }
 impl AsteroidFactory {
-    fn random(&self, phi: &mut Phi) -> Asteroid {
+    fn spawn(&self, phi: &mut Phi, spawn: &Spawn) -> Asteroid {
         let (w, h) = phi.output_size();
+        let side = self.side * spawn.scale;
+        let y = match spawn.y {
+            Some(y) => y * (h - side),
+            None => phi.rng.gen_range(0.0, (h - side).max(1.0)),
+        };
 
         // FPS in [10.0, 30.0)
         let mut sprite = self.sprite.clone();
-        sprite.set_fps(::rand::random::<f64>().abs() * 20.0 + 10.0);
+        sprite.set_fps(phi.rng.gen_range(10.0, 30.0));
 
         Asteroid {
             sprite: sprite,
@@ -93,14 +231,19 @@ impl AsteroidFactory {
             // In the screen vertically, and over the right of the screen
             // horizontally.
             rect: Rectangle {
-                w: ASTEROID_SIDE,
-                h: ASTEROID_SIDE,
+                w: side,
+                h: side,
                 x: w,
-                y: ::rand::random::<f64>().abs() * (h - ASTEROID_SIDE),
+                y: y,
             },
 
-            // vel in [50.0, 150.0)
-            vel: ::rand::random::<f64>().abs() * 100.0 + 50.0,
+            vel: (-spawn.speed, 0.0),
+            angle: phi.rng.gen_range(0.0, 360.0),
+            spin: phi.rng.gen_range(-MAX_SPIN, MAX_SPIN),
+            size: AsteroidSize::Large,
+            hit_points: AsteroidSize::Large.hit_points(),
+            large_side: side,
+            age: 0.0,
         }
     }
 }
@@ -128,7 +271,7 @@ impl Explosion {
         // Read the asteroid's image from the filesystem and construct an
         // animated sprite out of it.
 
-        let explosion_spritesheet = Sprite::load(&mut phi.renderer, EXPLOSION_PATH).unwrap();
+        let explosion_spritesheet = Sprite::load(&phi.renderer, EXPLOSION_PATH).unwrap();
         let mut explosion_sprites = Vec::with_capacity(EXPLOSIONS_TOTAL);
 
         for yth in 0..EXPLOSIONS_HIGH {
@@ -158,6 +301,10 @@ struct ExplosionFactory {
 }
 impl ExplosionFactory {
     fn at_center(&self, center: (f64, f64)) -> Explosion {
+        self.sized(center, EXPLOSION_SIDE)
+    }
+    /// An explosion `side` pixels wide, e.g. to match what blew up.
+    fn sized(&self, center: (f64, f64), side: f64) -> Explosion {
         // FPS in [10.0, 30.0)
         let sprite = self.sprite.clone();
 
@@ -166,7 +313,7 @@ impl ExplosionFactory {
 
             // In the screen vertically, and over the right of the screen
             // horizontally.
-            rect: Rectangle::with_size(EXPLOSION_SIDE, EXPLOSION_SIDE).center_at(center),
+            rect: Rectangle::with_size(side, side).center_at(center),
 
             alive_since: 0.0,
         }
@@ -188,13 +335,25 @@ enum PlayerFrame {
 struct Player {
     rect: Rectangle,
     sprites: Vec<Sprite>,
+    /// The solid pixels of every sprite, if they could be read.
+    masks: Vec<Option<Rc<Mask>>>,
     current: PlayerFrame,
-    cannon: CannonType,
+    weapon: Weapon,
+    /// Ships left, including the current one.
+    lives: u32,
+    /// Seconds until the ship respawns, if it has been destroyed.
+    respawn_in: Option<f64>,
+    /// Seconds during which the ship cannot be hit.
+    invulnerable_for: f64,
+    /// Seconds left to the shield and to the speed boost of power-ups.
+    shield_for: f64,
+    boost_for: f64,
+    tuning: Tuning,
 }
 impl Player {
-    pub fn new(phi: &mut Phi) -> Player {
+    pub fn new(phi: &mut Phi, tuning: Tuning) -> Player {
         // Get the spaceship's sprites
-        let spritesheet = Sprite::load(&mut phi.renderer, PLAYER_PATH).unwrap();
+        let spritesheet = Sprite::load(&phi.renderer, PLAYER_PATH).unwrap();
         let mut sprites = Vec::with_capacity(9);
 
         for y in 0..3 {
@@ -210,62 +369,186 @@ impl Player {
         }
 
         Player {
-            // Spawn the player at the center of the screen, vertically.
-            rect: Rectangle {
-                x: 64.0,
-                y: (phi.output_size().1 - PLAYER_H) / 2.0,
-                w: PLAYER_W,
-                h: PLAYER_H,
-            },
+            rect: Player::spawn_rect(phi),
+            masks: sprites.iter()
+                .map(|sprite| sprite.mask(ALPHA_THRESHOLD).map(Rc::new))
+                .collect(),
             sprites: sprites,
             current: PlayerFrame::MidNorm,
-            cannon: CannonType::RectBullet,
+            weapon: Weapon::new(tuning.charge_time),
+            lives: tuning.player_lives,
+            respawn_in: None,
+            invulnerable_for: 0.0,
+            shield_for: 0.0,
+            boost_for: 0.0,
+            tuning: tuning,
+        }
+    }
+    fn spawn_rect(phi: &mut Phi) -> Rectangle {
+        // Spawn the player at the center of the screen, vertically.
+        Rectangle {
+            x: 64.0,
+            y: (phi.output_size().1 - PLAYER_H) / 2.0,
+            w: PLAYER_W,
+            h: PLAYER_H,
+        }
+    }
+    /// Whether the ship is on the screen, as opposed to waiting to respawn.
+    pub fn is_alive(&self) -> bool {
+        self.respawn_in.is_none()
+    }
+    /// Whether an asteroid hitting the ship would destroy it.
+    pub fn is_vulnerable(&self) -> bool {
+        self.is_alive() && self.invulnerable_for <= 0.0 && self.shield_for <= 0.0
+    }
+    /// Whether the last ship has been destroyed and its explosion has
+    /// faded away, i.e. whether the game is over.
+    pub fn is_game_over(&self) -> bool {
+        self.lives == 0 && self.respawn_in.is_some_and(|t| t <= 0.0)
+    }
+    /// Destroys the ship, which respawns after a while if it has lives left.
+    /// Its weapon loses its upgrades.
+    pub fn kill(&mut self) {
+        self.lives -= 1;
+        self.weapon.reset();
+        self.boost_for = 0.0;
+        self.respawn_in = Some(self.tuning.respawn_delay);
+    }
+    /// Applies the effect of a power-up. Bombs are left to the game, as they
+    /// affect everything but the ship.
+    pub fn collect(&mut self, kind: PowerUpKind) {
+        match kind {
+            PowerUpKind::Weapon => {
+                self.weapon.power_up();
+            }
+            PowerUpKind::Shield => self.shield_for = SHIELD_DURATION,
+            PowerUpKind::ExtraLife => self.lives += 1,
+            PowerUpKind::Speed => self.boost_for = BOOST_DURATION,
+            PowerUpKind::Bomb => {}
         }
     }
     pub fn render(&self, phi: &mut Phi) {
+        if !self.is_alive() {
+            return;
+        }
+        // The shield flickers as it is about to run out
+        let flicker = self.shield_for < 2.0 && (self.shield_for * BLINK_FREQUENCY) as u32 % 2 == 1;
+        if self.shield_for > 0.0 && !flicker {
+            phi.renderer.set_draw_color(Color::RGB(60, 170, 255));
+            let shield = Rectangle::with_size(PLAYER_W + 12.0, PLAYER_H + 12.0)
+                .center_at(self.rect.center());
+            let _ = phi.renderer.draw_rect(shield.to_sdl().unwrap());
+        }
+        // Blink while invulnerable
+        if (self.invulnerable_for * BLINK_FREQUENCY) as u32 % 2 == 1 {
+            return;
+        }
         // Render the bounding box (for debugging purposes)
         if DEBUG {
             phi.renderer.set_draw_color(Color::RGB(200, 200, 50));
-            phi.renderer.fill_rect(self.rect.to_sdl().unwrap());
+            let _ = phi.renderer.fill_rect(self.rect.to_sdl().unwrap());
         }
         // Render the Player
         phi.renderer.copy_sprite(&self.sprites[self.current as usize], self.rect);
+        // A glow grows at the nose while the charge shot builds up
+        let charge = self.weapon.charge();
+        if charge > 0.0 {
+            let color = if charge >= 1.0 {
+                Color::RGB(255, 255, 255)
+            } else {
+                Color::RGB(230, 230, 30)
+            };
+            phi.renderer.set_draw_color(color);
+            let side = 2.0 + 10.0 * charge;
+            let _ = phi.renderer.fill_rect(Rectangle::with_size(side, side)
+                                               .center_at(self.nose())
+                                               .to_sdl()
+                                               .unwrap());
+        }
     }
-    fn spawn_bullets(&self) -> Vec<Box<Bullet>> {
-        let cannons_x = self.rect.x + 30.0;
-        let cannon1_y = self.rect.y + 6.0;
-        let cannon2_y = self.rect.y + PLAYER_H - 10.0;
-        spawn_bullets(self.cannon, cannons_x, cannon1_y, cannon2_y)
-
+    /// The outline of the ship, as currently drawn.
+    pub fn shape(&self) -> Shape {
+        match self.masks[self.current as usize] {
+            Some(ref mask) => Shape::Mask(mask.clone(), self.rect),
+            None => Shape::Rect(self.rect),
+        }
+    }
+    fn nose(&self) -> (f64, f64) {
+        (self.rect.x + PLAYER_W, self.rect.y + PLAYER_H / 2.0)
+    }
+    /// Keeps firing as long as the fire button is held, as fast as the
+    /// weapon allows, while building up a charge shot, which is fired when
+    /// the button is released after being held long enough.
+    fn spawn_bullets(&mut self, phi: &mut Phi, elapsed: f64) -> Vec<Box<dyn Bullet>> {
+        let nose = self.nose();
+        if phi.events.key_space {
+            self.weapon.hold(elapsed);
+            let cannons_x = self.rect.x + 30.0;
+            let cannon1_y = self.rect.y + 6.0;
+            let cannon2_y = self.rect.y + PLAYER_H - 10.0;
+            self.weapon.fire(self.tuning.bullet_speed, cannons_x, cannon1_y, cannon2_y, nose)
+        } else if phi.events.now.key_space == Some(false) {
+            self.weapon.release(self.tuning.bullet_speed, nose)
+        } else {
+            vec![]
+        }
     }
     pub fn update(&mut self, phi: &mut Phi, elapsed: f64) {
+        self.weapon.update(elapsed);
+        if let Some(respawn_in) = self.respawn_in {
+            let respawn_in = (respawn_in - elapsed).max(0.0);
+            if respawn_in > 0.0 || self.lives == 0 {
+                self.respawn_in = Some(respawn_in);
+                return;
+            }
+            self.respawn_in = None;
+            self.invulnerable_for = self.tuning.invulnerability;
+            self.rect = Player::spawn_rect(phi);
+            self.current = PlayerFrame::MidNorm;
+        }
+        self.invulnerable_for = (self.invulnerable_for - elapsed).max(0.0);
+        self.shield_for = (self.shield_for - elapsed).max(0.0);
+        self.boost_for = (self.boost_for - elapsed).max(0.0);
+
         if phi.events.now.key_1 == Some(true) {
-            self.cannon = CannonType::RectBullet;
+            self.weapon.select(CannonType::RectBullet);
         }
 
         if phi.events.now.key_2 == Some(true) {
-            self.cannon = CannonType::SineBullet {
+            self.weapon.select(CannonType::SineBullet {
                 amplitude: 10.0,
                 angular_vel: 15.0,
-            };
+            });
         }
 
         if phi.events.now.key_3 == Some(true) {
-            // TODO
-        }
-        let traveled = PLAYER_SPEED * elapsed;
-        let diagonal = (phi.events.key_up ^ phi.events.key_down) &&
-                       (phi.events.key_left ^ phi.events.key_right);
-        let moved = if diagonal { 1.0 / 2.0f64.sqrt() } else { 1.0 } * PLAYER_SPEED * elapsed;
-        let dx = match (phi.events.key_left, phi.events.key_right) {
-            (true, true) | (false, false) => 0.0,
-            (true, false) => -moved,
-            (false, true) => moved,
-        };
-        let dy = match (phi.events.key_up, phi.events.key_down) {
-            (true, true) | (false, false) => 0.0,
-            (true, false) => -moved,
-            (false, true) => moved,
+            self.weapon.select(CannonType::DivergentBullet {
+                a: 100.0,
+                b: 1.2,
+            });
+        }
+        let boost = if self.boost_for > 0.0 { BOOST_FACTOR } else { 1.0 };
+        let traveled = self.tuning.player_speed * boost * elapsed;
+        let digital = phi.events.key_up || phi.events.key_down || phi.events.key_left ||
+                      phi.events.key_right;
+        let (dx, dy) = if !digital && phi.events.stick != (0.0, 0.0) {
+            // The analog stick gives both the direction and the speed
+            (phi.events.stick.0 * traveled, phi.events.stick.1 * traveled)
+        } else {
+            let diagonal = (phi.events.key_up ^ phi.events.key_down) &&
+                           (phi.events.key_left ^ phi.events.key_right);
+            let moved = if diagonal { 1.0 / 2.0f64.sqrt() } else { 1.0 } * traveled;
+            let dx = match (phi.events.key_left, phi.events.key_right) {
+                (true, true) | (false, false) => 0.0,
+                (true, false) => -moved,
+                (false, true) => moved,
+            };
+            let dy = match (phi.events.key_up, phi.events.key_down) {
+                (true, true) | (false, false) => 0.0,
+                (true, false) => -moved,
+                (false, true) => moved,
+            };
+            (dx, dy)
         };
         self.rect.x += dx;
         self.rect.y += dy;
@@ -301,74 +584,221 @@ impl Player {
 }
 pub struct GameView {
     player: Player,
-    bullets: Vec<Box<Bullet>>,
+    bullets: Vec<Box<dyn Bullet>>,
     asteroids: Vec<Asteroid>,
+    enemies: Vec<Enemy>,
+    /// Bullets fired by enemies, which only hurt the player.
+    enemy_bullets: Vec<Box<dyn Bullet>>,
+    powerups: Vec<PowerUp>,
     bg: BgSet,
     asteroid_factory: AsteroidFactory,
+    enemy_factory: EnemyFactory,
+    powerup_factory: PowerUpFactory,
+    director: SpawnDirector,
+    /// The boss of the level, until it appears.
+    pending_boss: Option<BossDescr>,
+    boss: Option<Boss>,
+    /// Seconds since the game started.
+    time: f64,
+    /// Seconds until the game ends, once the level has been cleared.
+    cleared_in: Option<f64>,
+    clear_banner: Sprite,
+    /// How asteroids and power-ups move.
+    physics: Physics,
     explosions: Vec<Explosion>,
     explosion_factory: ExplosionFactory,
-    music: Music<'static>,
+    /// `None` if the music of the level could not be loaded, in which case
+    /// the game is silent.
+    music: Option<Music<'static>>,
     bullet_sound: Chunk,
     explosion_sound: Chunk,
+    score: Score,
+    hud: Hud,
+    /// Whether this game is being recorded, to be shown as the demo by the
+    /// main menu.
+    recording_demo: bool,
 }
 impl GameView {
+    /// Starts a game of `DEFAULT_LEVEL`.
     pub fn with_backgrounds(phi: &mut Phi, bg: BgSet) -> GameView {
-        let music = Music::from_file(Path::new("assets/mdk_phoenix_orchestral.ogg")).unwrap();
-        music.play(-1).unwrap();
+        let level = Level::load(Path::new(DEFAULT_LEVEL)).unwrap_or_else(|e| {
+            println!("Could not load the level, using the default one: {}", e);
+            Level::default()
+        });
+        GameView::with_level(phi, bg, level)
+    }
+
+    pub fn with_level(phi: &mut Phi, mut bg: BgSet, level: Level) -> GameView {
+        if let Some(ref backgrounds) = level.backgrounds {
+            if let Err(e) = bg.load(&mut phi.renderer, backgrounds) {
+                println!("Could not load the backgrounds of {}: {}", level.name, e);
+            }
+        }
+        let music = match Music::from_file(Path::new(&level.music)) {
+            Ok(music) => Some(music),
+            Err(e) => {
+                println!("Could not load the music of {}: {}", level.name, e);
+                None
+            }
+        };
         let bullet_sound = Chunk::from_file(Path::new("assets/bullet.ogg")).unwrap();
 
         let explosion_sound = Chunk::from_file(Path::new("assets/explosion.ogg")).unwrap();
         GameView {
-            player: Player::new(phi),
+            player: Player::new(phi, level.tuning.clone()),
             bullets: vec![],
             asteroids: vec![],
+            enemies: vec![],
+            enemy_bullets: vec![],
+            powerups: vec![],
             bg: bg,
-            asteroid_factory: Asteroid::factory(phi),
+            asteroid_factory: Asteroid::factory(phi, level.tuning.asteroid_side),
+            enemy_factory: EnemyFactory::new(phi),
+            powerup_factory: PowerUpFactory::new(phi, level.tuning.powerup_chance),
+            director: SpawnDirector::new(level.waves, level.spawns),
+            pending_boss: level.boss,
+            boss: None,
+            time: 0.0,
+            cleared_in: None,
+            clear_banner: phi.ttf_str_sprite("Level Clear",
+                                             "assets/belligerent.ttf",
+                                             64,
+                                             Color::RGB(216, 21, 21))
+                .unwrap(),
+            physics: level.physics,
             explosions: vec![],
             explosion_factory: Explosion::factory(phi),
             music: music,
             bullet_sound: bullet_sound,
             explosion_sound: explosion_sound,
+            score: Score::new(),
+            hud: Hud::new(),
+            recording_demo: false,
+        }
+    }
+
+    /// Destroys every asteroid, enemy and enemy bullet on the screen. The
+    /// boss is too tough to be harmed.
+    fn detonate_bomb(&mut self, phi: &mut Phi) {
+        for asteroid in ::std::mem::take(&mut self.asteroids) {
+            self.score.asteroid_destroyed(asteroid.rect.w, asteroid.speed());
+            let explosion = self.explosion_factory.sized(asteroid.rect.center(), asteroid.rect.w);
+            self.explosions.push(explosion);
+        }
+        for enemy in ::std::mem::take(&mut self.enemies) {
+            self.score.enemy_destroyed();
+            self.explosions.push(self.explosion_factory.at_center(enemy.rect().center()));
+        }
+        self.enemy_bullets.clear();
+        phi.play_sound(&self.explosion_sound);
+    }
+
+    /// Leaves the game, letting the player enter their name if their score
+    /// is high enough. Otherwise, shows `title` along with the score.
+    fn end(&mut self, phi: &mut Phi, title: &str) -> ViewAction {
+        let bg = self.bg.clone();
+        let score = self.score.points();
+        if HighScores::load().qualifies(score) {
+            ViewAction::Replace(Box::new(NameEntryView::new(phi, bg, score)))
+        } else {
+            ViewAction::Replace(Box::new(::views::game_over::GameOverView::new(phi, bg, score, title)))
         }
     }
 }
+
 impl View for GameView {
-    fn render(&mut self, phi: &mut Phi, elapsed: f64) -> ViewAction {
+    fn enter(&mut self, phi: &mut Phi) {
+        if let Some(ref music) = self.music {
+            if let Err(e) = music.play(-1) {
+                println!("Could not play the music: {}", e);
+            }
+        }
+
+        // Only the game which was asked for becomes the demo, unless the
+        // whole session is already being recorded.
+        if phi.record_demo && !phi.events.is_replaying() && !phi.events.is_recording() {
+            phi.record_demo = false;
+            if let Some(path) = ::phi::user_file(DEMO_FILE) {
+                match phi.start_recording(&path) {
+                    Ok(()) => self.recording_demo = true,
+                    Err(e) => println!("Could not record the demo: {}", e),
+                }
+            }
+        }
+    }
+
+    fn exit(&mut self, phi: &mut Phi) {
+        Music::halt();
+        if self.recording_demo {
+            phi.events.stop_recording();
+        }
+    }
+
+    fn update(&mut self, phi: &mut Phi, elapsed: f64) -> ViewAction {
         if phi.events.now.key_escape == Some(true) {
             let bg = self.bg.clone();
-            return ViewAction::ChangeView(Box::new(::views::main_menu::MainMenuView::with_backgrounds(phi,bg)));
+            return ViewAction::Push(Box::new(::views::pause::PauseView::new(phi, bg)));
         }
 
-
-        // Clear the screen
-        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
-        phi.renderer.clear();
-        // Render the Backgrounds
-        self.bg.back.render(&mut phi.renderer, elapsed);
-        self.bg.middle.render(&mut phi.renderer, elapsed);
+        // Update the Backgrounds
+        self.bg.update(elapsed);
 
 
         // Update the player
         self.player.update(phi, elapsed);
+        // Once the level is cleared, losing the last ship no longer matters
+        if self.player.is_game_over() && self.cleared_in.is_none() {
+            return self.end(phi, "Game Over");
+        }
+        self.time += elapsed;
+        if let Some(cleared_in) = self.cleared_in {
+            if cleared_in <= 0.0 {
+                return self.end(phi, "Level Clear");
+            }
+            self.cleared_in = Some(cleared_in - elapsed);
+        }
         let mut player_alive = true;
+        let player_shape = self.player.shape();
         // Update the Bullet pos
-        self.bullets = ::std::mem::replace(&mut self.bullets, vec![])
+        self.bullets = ::std::mem::take(&mut self.bullets)
             .into_iter()
             .filter_map(|bullet| bullet.update(phi, elapsed))
             .collect();
         // Update the Asteroids
-
-        self.asteroids = ::std::mem::replace(&mut self.asteroids, vec![])
+        let screen = phi.output_size();
+        self.asteroids = ::std::mem::take(&mut self.asteroids)
             .into_iter()
-            .filter_map(|asteroid| asteroid.update(elapsed))
+            .filter_map(|asteroid| asteroid.update(&self.physics, screen, elapsed))
+            .collect();
+        // Update the enemies, which may fire at the player
+        let target = if self.player.is_alive() {
+            Some(self.player.rect.center())
+        } else {
+            None
+        };
+        let mut enemy_bullets = ::std::mem::take(&mut self.enemy_bullets)
+            .into_iter()
+            .filter_map(|bullet| bullet.update(phi, elapsed))
             .collect();
+        self.enemies = ::std::mem::take(&mut self.enemies)
+            .into_iter()
+            .filter_map(|enemy| enemy.update(phi, elapsed, target, &mut enemy_bullets))
+            .collect();
+        if self.pending_boss.as_ref().is_some_and(|boss| self.time >= boss.time) {
+            let descr = self.pending_boss.take().unwrap();
+            self.boss = Some(Boss::new(phi, &descr));
+        }
+        if let Some(ref mut boss) = self.boss {
+            boss.update(phi, elapsed, target, &mut enemy_bullets);
+        }
+        self.enemy_bullets = enemy_bullets;
         // Update the explosions
-        self.explosions = ::std::mem::replace(&mut self.explosions, vec![])
+        self.explosions = ::std::mem::take(&mut self.explosions)
             .into_iter()
             .filter_map(|explosion| explosion.update(elapsed))
             .collect();
         //can keep track of which got into a collision
-        let mut transition_bullets: Vec<_> = ::std::mem::replace(&mut self.bullets, vec![])
+        let mut transition_bullets: Vec<_> = ::std::mem::take(&mut self.bullets)
             .into_iter()
             .map(|bullet| {
                      MaybeAlive {
@@ -378,25 +808,50 @@ impl View for GameView {
                  })
             .collect();
 
-        self.asteroids = ::std::mem::replace(&mut self.asteroids, vec![])
+        // Only the bullets which went near an asteroid may hit it
+        let mut grid = Grid::new(BROAD_PHASE_CELL);
+        for asteroid in &self.asteroids {
+            grid.insert(asteroid.shape().bounds());
+        }
+        let bullet_rects: Vec<Rectangle> = transition_bullets.iter()
+            .map(|bullet| bullet.value.rect().swept(bullet.value.motion()))
+            .collect();
+        let mut nearby_bullets = vec![vec![]; self.asteroids.len()];
+        for (asteroid, bullet) in grid.pairs(&bullet_rects) {
+            nearby_bullets[asteroid].push(bullet);
+        }
+
+        let mut fragments = vec![];
+        self.asteroids = ::std::mem::take(&mut self.asteroids)
             .into_iter()
-            .filter_map(|asteroid| {
+            .zip(nearby_bullets)
+            .filter_map(|(mut asteroid, nearby_bullets)| {
                 let mut asteroid_alive = true;
 
-                for bullet in &mut transition_bullets {
+                for i in nearby_bullets {
                     //? Notice that we refer to the bullet as `bullet.value`
                     //? because it has been wrapped in `MaybeAlive`.
-                    if asteroid.rect().overlaps(bullet.value.rect()) {
-                        asteroid_alive = false;
+                    let bullet = &mut transition_bullets[i];
+                    if asteroid_alive && bullet.alive &&
+                       asteroid.shape().hit_by(bullet.value.rect(), bullet.value.motion()) {
+                        asteroid_alive = !asteroid.hit(bullet.value.damage());
                         //? We go through every bullet and "kill" those that collide
                         //? with the asteroid. We do this for every asteroid.
                         bullet.alive = false;
                     }
                 }
+                if !asteroid_alive {
+                    self.score.asteroid_destroyed(asteroid.rect.w, asteroid.speed());
+                    if let Some(powerup) = self.powerup_factory
+                        .maybe_drop(phi, asteroid.rect.center(), asteroid.speed()) {
+                        self.powerups.push(powerup);
+                    }
+                    fragments.append(&mut asteroid.split(phi));
+                }
 
                 // The player's Player is destroyed if it is hit by an asteroid.
                 // In which case, the asteroid is also destroyed.
-                if asteroid.rect().overlaps(self.player.rect) {
+                if self.player.is_vulnerable() && asteroid.shape().overlaps(&player_shape) {
                     asteroid_alive = false;
                     player_alive = false;
                 }
@@ -407,28 +862,171 @@ impl View for GameView {
                     Some(asteroid)
                 } else {
 
-                    self.explosions.push(self.explosion_factory.at_center(asteroid.rect()
-                                                                              .center()));
+                    self.explosions.push(self.explosion_factory.sized(asteroid.rect().center(),
+                                                                      asteroid.rect().w));
+
+
+                    phi.play_sound(&self.explosion_sound);
+                    None
+                }
+            })
+            .collect();
+        self.asteroids.append(&mut fragments);
+
+        self.enemies = ::std::mem::take(&mut self.enemies)
+            .into_iter()
+            .filter_map(|enemy| {
+                let mut enemy_alive = true;
+                for bullet in &mut transition_bullets {
+                    if enemy_alive && bullet.alive &&
+                       enemy.shape().hit_by(bullet.value.rect(), bullet.value.motion()) {
+                        enemy_alive = false;
+                        bullet.alive = false;
+                    }
+                }
+                if !enemy_alive {
+                    self.score.enemy_destroyed();
+                }
 
+                // Ramming the player destroys both ships
+                if self.player.is_vulnerable() && enemy.shape().overlaps(&player_shape) {
+                    enemy_alive = false;
+                    player_alive = false;
+                }
 
+                if enemy_alive {
+                    Some(enemy)
+                } else {
+                    self.explosions.push(self.explosion_factory.at_center(enemy.rect().center()));
                     phi.play_sound(&self.explosion_sound);
                     None
                 }
             })
             .collect();
 
-        self.bullets = transition_bullets.into_iter().filter_map(MaybeAlive::as_option).collect();
+        // Bullets hitting the boss damage the part they hit, if it is exposed
+        let mut boss_destroyed = false;
+        if let Some(ref mut boss) = self.boss {
+            for bullet in &mut transition_bullets {
+                if !bullet.alive {
+                    continue;
+                }
+                let rect = bullet.value.rect();
+                match boss.hit(rect, bullet.value.motion(), bullet.value.damage()) {
+                    Hit::Miss => continue,
+                    Hit::Blocked | Hit::Damaged => {}
+                    Hit::TurretDestroyed => {
+                        self.score.enemy_destroyed();
+                        self.explosions.push(self.explosion_factory.at_center(rect.center()));
+                        phi.play_sound(&self.explosion_sound);
+                    }
+                    Hit::Destroyed => {
+                        self.score.boss_destroyed();
+                        boss_destroyed = true;
+                    }
+                }
+                bullet.alive = false;
+                if boss_destroyed {
+                    break;
+                }
+            }
+
+            if self.player.is_vulnerable() &&
+               boss.hitboxes().into_iter().any(|rect| Shape::Rect(rect).overlaps(&player_shape)) {
+                player_alive = false;
+            }
+        }
+        if boss_destroyed {
+            // Blow up the whole hull
+            for rect in self.boss.take().unwrap().hitboxes() {
+                self.explosions.push(self.explosion_factory.at_center(rect.center()));
+            }
+            phi.play_sound(&self.explosion_sound);
+            self.enemy_bullets.clear();
+            self.cleared_in = Some(LEVEL_CLEAR_DELAY);
+        }
+
+        // Enemy bullets vanish when they hit the player
+        if self.player.is_vulnerable() {
+            let before = self.enemy_bullets.len();
+            self.enemy_bullets
+                .retain(|bullet| !player_shape.hit_by(bullet.rect(), bullet.motion()));
+            if self.enemy_bullets.len() < before {
+                player_alive = false;
+            }
+        }
+
+        self.bullets = transition_bullets.into_iter().filter_map(MaybeAlive::into_option).collect();
         if !player_alive {
-            println!("The player's Player has been destroyed.");
+            self.score.player_hit();
+            self.player.kill();
+            self.explosions.push(self.explosion_factory.at_center(self.player.rect.center()));
         }
-        if phi.events.now.key_space == Some(true) {
-            self.bullets.append(&mut self.player.spawn_bullets());
-            phi.play_sound(&self.bullet_sound);
+        if self.player.is_alive() {
+            let mut volley = self.player.spawn_bullets(phi, elapsed);
+            if !volley.is_empty() {
+                self.bullets.append(&mut volley);
+                phi.play_sound(&self.bullet_sound);
+            }
         }
-        if ::rand::random::<usize>() % 100 == 0 {
-            self.asteroids.push(self.asteroid_factory.random(phi));
+        // The waves stop once the boss shows up
+        let waves_over = self.boss.is_some() || self.cleared_in.is_some();
+        let spawns = if waves_over {
+            vec![]
+        } else {
+            self.director.update(&mut phi.rng, elapsed)
+        };
+        for spawn in spawns {
+            match spawn.enemy {
+                EnemyKind::Asteroid => {
+                    let asteroid = self.asteroid_factory.spawn(phi, &spawn);
+                    self.asteroids.push(asteroid);
+                }
+                _ => {
+                    let enemy = self.enemy_factory.spawn(phi, &spawn, self.player.rect.center());
+                    self.enemies.push(enemy);
+                }
+            }
         }
-        println!("{}", self.asteroids.len());
+
+        // Power-ups drift away unless the ship collects them
+        self.powerups = ::std::mem::take(&mut self.powerups)
+            .into_iter()
+            .filter_map(|powerup| powerup.update(&self.physics, screen, elapsed))
+            .collect();
+        if self.player.is_alive() {
+            let player_rect = self.player.rect;
+            let (collected, left): (Vec<_>, Vec<_>) = ::std::mem::take(&mut self.powerups)
+                .into_iter()
+                .partition(|powerup| powerup.rect().overlaps(player_rect));
+            self.powerups = left;
+            for powerup in collected {
+                match powerup.kind() {
+                    PowerUpKind::Bomb => self.detonate_bomb(phi),
+                    kind => self.player.collect(kind),
+                }
+            }
+        }
+
+        // Update the score, and the HUD which displays it
+        if self.player.is_alive() {
+            self.score.update(elapsed);
+        }
+        self.hud.update(phi, self.score.points(), self.score.multiplier(), self.player.lives);
+        self.hud.set_weapon(phi, &self.player.weapon);
+        self.hud.set_boss_health(self.boss.as_ref().map(Boss::health));
+
+        ViewAction::None
+    }
+
+    fn draw(&self, phi: &mut Phi, alpha: f64) {
+        // Clear the screen
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+        // Render the Backgrounds
+        self.bg.back.render(&mut phi.renderer, alpha);
+        self.bg.middle.render(&mut phi.renderer, alpha);
+        self.physics.render(phi);
 
         //Render the player
         self.player.render(phi);
@@ -437,17 +1035,44 @@ impl View for GameView {
             asteroid.render(phi);
         }
 
+        for powerup in &self.powerups {
+            powerup.render(phi);
+        }
+
+        //Render the enemies
+        for enemy in &self.enemies {
+            enemy.render(phi);
+        }
+
+        if let Some(ref boss) = self.boss {
+            boss.render(phi);
+        }
+
         //Render the bullets
         for bullet in &self.bullets {
             bullet.render(phi);
         }
+        for bullet in &self.enemy_bullets {
+            bullet.render(phi);
+        }
         //Render Explosion
         for explosion in &self.explosions {
             explosion.render(phi);
         }
         // Render the foreground
-        self.bg.front.render(&mut phi.renderer, elapsed);
-
-        ViewAction::None
+        self.bg.front.render(&mut phi.renderer, alpha);
+        // Render the HUD
+        self.hud.render(phi);
+        if self.cleared_in.is_some() {
+            let (win_w, win_h) = phi.output_size();
+            let (w, h) = self.clear_banner.size();
+            phi.renderer.copy_sprite(&self.clear_banner,
+                                     Rectangle {
+                                         x: (win_w - w) / 2.0,
+                                         y: (win_h - h) / 2.0,
+                                         w: w,
+                                         h: h,
+                                     });
+        }
     }
 }
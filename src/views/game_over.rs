@@ -0,0 +1,91 @@
+use phi::{Phi, View, ViewAction};
+use phi::gfx::{CopySprite, Sprite};
+use phi::data::Rectangle;
+use sdl2::pixels::Color;
+use views::menu::{Action, Menu};
+use views::shared::BgSet;
+
+/// Shown once the player has lost every life, or cleared the level.
+pub struct GameOverView {
+    title: Sprite,
+    score: Sprite,
+    menu: Menu,
+    bg: BgSet,
+}
+
+impl GameOverView {
+    /// `title` tells how the game ended.
+    pub fn new(phi: &mut Phi, bg: BgSet, score: u32, title: &str) -> GameOverView {
+        GameOverView {
+            title: phi.ttf_str_sprite(title, "assets/belligerent.ttf", 64, Color::RGB(216, 21, 21))
+                .unwrap(),
+            score: phi.ttf_str_sprite(&format!("Score: {}", score),
+                                 "assets/belligerent.ttf",
+                                 32,
+                                 Color::RGB(255, 255, 255))
+                .unwrap(),
+            menu: Menu::new(vec![Action::new(phi,
+                                             "Play Again",
+                                             Box::new(|phi, bg| {
+                                                          ViewAction::Replace(Box::new(
+                        ::views::game::GameView::with_backgrounds(phi, bg)))
+                                                      })),
+                                 Action::new(phi,
+                                             "Main Menu",
+                                             Box::new(|phi, bg| {
+                                                          ViewAction::Replace(Box::new(
+                        ::views::main_menu::MainMenuView::with_backgrounds(phi, bg)))
+                                                      }))]),
+            bg: bg,
+        }
+    }
+}
+
+impl View for GameOverView {
+    fn update(&mut self, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+        if phi.events.now.key_escape == Some(true) {
+            let bg = self.bg.clone();
+            return ViewAction::Replace(Box::new(::views::main_menu::MainMenuView::with_backgrounds(phi, bg)));
+        }
+
+        if let Some((_, action)) = self.menu.update(phi, &self.bg) {
+            return action;
+        }
+
+        self.bg.update(elapsed);
+        ViewAction::None
+    }
+
+    fn draw(&self, phi: &mut Phi, alpha: f64) {
+        // Clear the screen
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+        // Render the background
+        self.bg.back.render(&mut phi.renderer, alpha);
+        self.bg.middle.render(&mut phi.renderer, alpha);
+        self.bg.front.render(&mut phi.renderer, alpha);
+
+        // Render the title and the final score above the menu
+        let (win_w, win_h) = phi.output_size();
+        let (w, h) = self.title.size();
+        phi.renderer.copy_sprite(&self.title,
+                                 Rectangle {
+                                     x: (win_w - w) / 2.0,
+                                     y: win_h / 6.0,
+                                     w: w,
+                                     h: h,
+                                 });
+        let (score_w, score_h) = self.score.size();
+        phi.renderer.copy_sprite(&self.score,
+                                 Rectangle {
+                                     x: (win_w - score_w) / 2.0,
+                                     y: win_h / 6.0 + h + 10.0,
+                                     w: score_w,
+                                     h: score_h,
+                                 });
+        self.menu.render(phi);
+    }
+}
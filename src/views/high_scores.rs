@@ -0,0 +1,278 @@
+use phi::{Phi, View, ViewAction};
+use phi::gfx::{CopySprite, Sprite};
+use phi::data::Rectangle;
+use sdl2::pixels::Color;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use views::shared::BgSet;
+
+const FILE_NAME: &str = "highscores.txt";
+/// First line of the file. Bump the version whenever the format changes, so
+/// that older tables are ignored instead of misread.
+const HEADER: &str = "arcaders high scores v1";
+const MAX_ENTRIES: usize = 10;
+
+/// The best scores ever achieved, highest first.
+///
+/// They are stored in the user's data directory as a plain text file, which
+/// starts with `HEADER` and lists every entry as `<score>\t<name>` on its own
+/// line.
+pub struct HighScores {
+    entries: Vec<(String, u32)>,
+}
+
+impl HighScores {
+    /// Reads the table from the disk. A missing or unreadable file is treated
+    /// as an empty table.
+    pub fn load() -> HighScores {
+        let mut scores = HighScores { entries: vec![] };
+        let path = match ::phi::user_file(FILE_NAME) {
+            Some(path) => path,
+            None => return scores,
+        };
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(_) => return scores,
+        };
+
+        let mut lines = BufReader::new(file).lines();
+        match lines.next() {
+            Some(Ok(ref header)) if header == HEADER => {}
+            _ => {
+                println!("Ignoring {}: unknown high score format", path.display());
+                return scores;
+            }
+        }
+        for (i, line) in lines.enumerate() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            let mut fields = line.splitn(2, '\t');
+            match (fields.next().and_then(|s| s.parse().ok()), fields.next()) {
+                (Some(score), Some(name)) => scores.entries.push((name.to_string(), score)),
+                _ => println!("{}:{}: malformed high score entry", path.display(), i + 2),
+            }
+        }
+
+        scores.entries.sort_by_key(|e| ::std::cmp::Reverse(e.1));
+        scores.entries.truncate(MAX_ENTRIES);
+        scores
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = ::phi::user_file(FILE_NAME)
+            .ok_or(io::Error::new(io::ErrorKind::NotFound, "no user data directory"))?;
+        let mut file = File::create(path)?;
+        writeln!(file, "{}", HEADER)?;
+        for &(ref name, score) in &self.entries {
+            writeln!(file, "{}\t{}", score, name)?;
+        }
+        Ok(())
+    }
+
+    pub fn entries(&self) -> &[(String, u32)] {
+        &self.entries
+    }
+
+    /// Whether `score` would make it into the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0 &&
+        (self.entries.len() < MAX_ENTRIES || self.entries.last().is_none_or(|e| score > e.1))
+    }
+
+    /// Adds an entry to the table, and returns its rank if it was kept.
+    pub fn insert(&mut self, name: &str, score: u32) -> Option<usize> {
+        // Older entries win ties
+        let rank = self.entries.iter().position(|e| score > e.1).unwrap_or(self.entries.len());
+        if rank >= MAX_ENTRIES {
+            return None;
+        }
+        self.entries.insert(rank, (name.to_string(), score));
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+}
+
+/// Displays the high score table over the backgrounds.
+pub struct HighScoresView {
+    title: Sprite,
+    /// The name and the score of every entry.
+    rows: Vec<(Sprite, Sprite)>,
+    bg: BgSet,
+}
+
+impl HighScoresView {
+    /// `highlight` is the rank of an entry to draw in a different color,
+    /// typically the one which has just been added.
+    pub fn new(phi: &mut Phi, bg: BgSet, highlight: Option<usize>) -> HighScoresView {
+        let scores = HighScores::load();
+        let rows = scores.entries()
+            .iter()
+            .enumerate()
+            .map(|(i, &(ref name, score))| {
+                let color = if highlight == Some(i) {
+                    Color::RGB(216, 21, 21)
+                } else {
+                    Color::RGB(255, 255, 255)
+                };
+                (phi.ttf_str_sprite(&format!("{}. {}", i + 1, name),
+                                    "assets/belligerent.ttf",
+                                    28,
+                                    color)
+                     .unwrap(),
+                 phi.ttf_str_sprite(&score.to_string(), "assets/belligerent.ttf", 28, color)
+                     .unwrap())
+            })
+            .collect();
+
+        HighScoresView {
+            title: phi.ttf_str_sprite("High Scores",
+                                 "assets/belligerent.ttf",
+                                 48,
+                                 Color::RGB(216, 21, 21))
+                .unwrap(),
+            rows: rows,
+            bg: bg,
+        }
+    }
+}
+
+impl View for HighScoresView {
+    fn update(&mut self, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+        if phi.events.now.key_escape == Some(true) || phi.events.now.key_enter == Some(true) ||
+           phi.events.now.key_space == Some(true) {
+            let bg = self.bg.clone();
+            return ViewAction::Replace(Box::new(::views::main_menu::MainMenuView::with_backgrounds(phi, bg)));
+        }
+
+        self.bg.update(elapsed);
+        ViewAction::None
+    }
+
+    fn draw(&self, phi: &mut Phi, alpha: f64) {
+        // Clear the screen
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+        // Render the background
+        self.bg.back.render(&mut phi.renderer, alpha);
+        self.bg.middle.render(&mut phi.renderer, alpha);
+        self.bg.front.render(&mut phi.renderer, alpha);
+
+        // Definitions for the table layout
+        let (win_w, win_h) = phi.output_size();
+        let row_h = 36.0;
+        let box_w = 400.0;
+        let padding = 20.0;
+        let (title_w, title_h) = self.title.size();
+        let box_h = title_h + padding * 3.0 + row_h * MAX_ENTRIES as f64;
+        let box_x = (win_w - box_w) / 2.0;
+        let box_y = (win_h - box_h) / 2.0;
+
+        // Render the colored box which holds the table
+        phi.renderer.set_draw_color(Color::RGB(70, 15, 70));
+        let _ = phi.renderer.fill_rect(Rectangle {
+                                               w: box_w + 6.0,
+                                               h: box_h + 6.0,
+                                               x: box_x - 3.0,
+                                               y: box_y - 3.0,
+                                           }
+                                           .to_sdl()
+                                           .unwrap());
+        phi.renderer.set_draw_color(Color::RGB(140, 30, 140));
+        let _ = phi.renderer.fill_rect(Rectangle {
+                                               w: box_w,
+                                               h: box_h,
+                                               x: box_x,
+                                               y: box_y,
+                                           }
+                                           .to_sdl()
+                                           .unwrap());
+
+        phi.renderer.copy_sprite(&self.title,
+                                 Rectangle {
+                                     x: (win_w - title_w) / 2.0,
+                                     y: box_y + padding,
+                                     w: title_w,
+                                     h: title_h,
+                                 });
+
+        // Names are aligned on the left, and scores on the right
+        let top = box_y + title_h + padding * 2.0;
+        for (i, (name, score)) in self.rows.iter().enumerate() {
+            let y = top + row_h * i as f64;
+            let (name_w, name_h) = name.size();
+            phi.renderer.copy_sprite(name,
+                                     Rectangle {
+                                         x: box_x + padding,
+                                         y: y,
+                                         w: name_w,
+                                         h: name_h,
+                                     });
+            let (score_w, score_h) = score.size();
+            phi.renderer.copy_sprite(score,
+                                     Rectangle {
+                                         x: box_x + box_w - padding - score_w,
+                                         y: y,
+                                         w: score_w,
+                                         h: score_h,
+                                     });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(scores: &[u32]) -> HighScores {
+        let mut table = HighScores { entries: vec![] };
+        for (i, &score) in scores.iter().enumerate() {
+            table.insert(&format!("P{}", i), score);
+        }
+        table
+    }
+
+    #[test]
+    fn entries_are_sorted_highest_first() {
+        let mut scores = table(&[100, 300]);
+        assert_eq!(scores.insert("C", 200), Some(1));
+        assert_eq!(scores.insert("D", 400), Some(0));
+        assert_eq!(scores.insert("E", 50), Some(4));
+        let points: Vec<u32> = scores.entries().iter().map(|e| e.1).collect();
+        assert_eq!(points, vec![400, 300, 200, 100, 50]);
+        assert_eq!(scores.entries()[0].0, "D");
+    }
+
+    #[test]
+    fn older_entries_win_ties() {
+        let mut scores = table(&[100]);
+        assert_eq!(scores.insert("New", 100), Some(1));
+        assert_eq!(scores.entries()[0].0, "P0");
+    }
+
+    #[test]
+    fn only_the_best_scores_are_kept() {
+        let mut scores = table(&[100; MAX_ENTRIES]);
+        assert!(!scores.qualifies(100));
+        assert!(!scores.qualifies(50));
+        assert_eq!(scores.insert("Late", 100), None);
+        assert_eq!(scores.entries().len(), MAX_ENTRIES);
+
+        assert!(scores.qualifies(101));
+        assert_eq!(scores.insert("Best", 101), Some(0));
+        assert_eq!(scores.entries().len(), MAX_ENTRIES);
+        assert_eq!(scores.entries()[MAX_ENTRIES - 1].0, format!("P{}", MAX_ENTRIES - 2));
+    }
+
+    #[test]
+    fn any_points_make_it_into_a_table_with_room() {
+        let scores = table(&[100, 200]);
+        assert!(scores.qualifies(1));
+        assert!(!scores.qualifies(0));
+        assert!(!table(&[]).qualifies(0));
+    }
+}
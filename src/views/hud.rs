@@ -0,0 +1,178 @@
+use phi::Phi;
+use phi::gfx::{CopySprite, Sprite};
+use phi::data::Rectangle;
+use sdl2::pixels::Color;
+use views::weapons::Weapon;
+
+const FONT_PATH: &str = "assets/belligerent.ttf";
+const FONT_SIZE: i32 = 24;
+const MARGIN: f64 = 10.0;
+const BOSS_BAR_W: f64 = 300.0;
+const BOSS_BAR_H: f64 = 12.0;
+const HEAT_BAR_W: f64 = 100.0;
+const HEAT_BAR_H: f64 = 6.0;
+
+/// A line of text which is only rendered to a texture when it changes.
+struct Counter {
+    text: String,
+    color: Color,
+    sprite: Option<Sprite>,
+}
+
+impl Counter {
+    fn new() -> Counter {
+        Counter {
+            text: String::new(),
+            color: Color::RGB(0, 0, 0),
+            sprite: None,
+        }
+    }
+
+    fn set(&mut self, phi: &mut Phi, text: &str, color: Color) {
+        if self.text != text || self.color != color {
+            self.text = text.to_string();
+            self.color = color;
+            self.sprite = phi.ttf_str_sprite(text, FONT_PATH, FONT_SIZE, color);
+        }
+    }
+}
+
+/// Heads-up display showing the score, the multiplier, the lives left and the
+/// player's weapon, along with the health of the boss while one is around.
+pub struct Hud {
+    score: Counter,
+    multiplier: Counter,
+    lives: Counter,
+    weapon: Counter,
+    /// Heat of the weapon, from 0 to 1, and whether it overheated.
+    heat: (f64, bool),
+    /// Health of the boss, from 0 to 1.
+    boss: Option<f64>,
+}
+
+impl Default for Hud {
+    fn default() -> Hud {
+        Hud::new()
+    }
+}
+
+impl Hud {
+    pub fn new() -> Hud {
+        Hud {
+            score: Counter::new(),
+            multiplier: Counter::new(),
+            lives: Counter::new(),
+            weapon: Counter::new(),
+            heat: (0.0, false),
+            boss: None,
+        }
+    }
+
+    pub fn update(&mut self, phi: &mut Phi, score: u32, multiplier: u32, lives: u32) {
+        self.score.set(phi, &format!("Score {}", score), Color::RGB(255, 255, 255));
+        // Highlight the multiplier while a chain is going on
+        let multiplier_color = if multiplier > 1 {
+            Color::RGB(230, 230, 30)
+        } else {
+            Color::RGB(255, 255, 255)
+        };
+        self.multiplier.set(phi, &format!("x{}", multiplier), multiplier_color);
+        self.lives.set(phi, &format!("Lives {}", lives), Color::RGB(255, 255, 255));
+    }
+
+    pub fn set_weapon(&mut self, phi: &mut Phi, weapon: &Weapon) {
+        // The weapon turns red while it cools down from overheating
+        let color = if weapon.is_overheated() {
+            Color::RGB(216, 21, 21)
+        } else {
+            Color::RGB(255, 255, 255)
+        };
+        self.weapon.set(phi, &format!("{} Lv{}", weapon.name(), weapon.power()), color);
+        self.heat = (weapon.heat(), weapon.is_overheated());
+    }
+
+    /// Shows the health of the boss, from 0 to 1, or hides it if `None`.
+    pub fn set_boss_health(&mut self, health: Option<f64>) {
+        self.boss = health;
+    }
+
+    pub fn render(&self, phi: &mut Phi) {
+        let (win_w, _) = phi.output_size();
+
+        // The boss' health bar is centered, under the counters
+        if let Some(health) = self.boss {
+            let bar = Rectangle {
+                x: (win_w - BOSS_BAR_W) / 2.0,
+                y: MARGIN * 4.0,
+                w: BOSS_BAR_W,
+                h: BOSS_BAR_H,
+            };
+            phi.renderer.set_draw_color(Color::RGB(70, 15, 70));
+            let _ = phi.renderer.fill_rect(Rectangle {
+                                                   x: bar.x - 2.0,
+                                                   y: bar.y - 2.0,
+                                                   w: bar.w + 4.0,
+                                                   h: bar.h + 4.0,
+                                               }
+                                               .to_sdl()
+                                               .unwrap());
+            if health > 0.0 {
+                phi.renderer.set_draw_color(Color::RGB(216, 21, 21));
+                let _ = phi.renderer.fill_rect(Rectangle { w: bar.w * health.min(1.0), ..bar }
+                                                   .to_sdl()
+                                                   .unwrap());
+            }
+        }
+
+        // The lives, then the weapon and its heat, are left-aligned
+        let mut top = MARGIN;
+        for counter in &[&self.lives, &self.weapon] {
+            if let Some(ref sprite) = counter.sprite {
+                let (w, h) = sprite.size();
+                phi.renderer.copy_sprite(sprite,
+                                         Rectangle {
+                                             x: MARGIN,
+                                             y: top,
+                                             w: w,
+                                             h: h,
+                                         });
+                top += h;
+            }
+        }
+        let bar = Rectangle {
+            x: MARGIN,
+            y: top + MARGIN / 2.0,
+            w: HEAT_BAR_W,
+            h: HEAT_BAR_H,
+        };
+        phi.renderer.set_draw_color(Color::RGB(40, 40, 40));
+        let _ = phi.renderer.fill_rect(bar.to_sdl().unwrap());
+        let (heat, overheated) = self.heat;
+        if heat > 0.0 {
+            let color = if overheated {
+                Color::RGB(216, 21, 21)
+            } else {
+                Color::RGB(230, 150, 30)
+            };
+            phi.renderer.set_draw_color(color);
+            let _ = phi.renderer.fill_rect(Rectangle { w: bar.w * heat.min(1.0), ..bar }.to_sdl().unwrap());
+        }
+
+        // The score is right-aligned, and followed by the multiplier
+        let mut right = win_w - MARGIN;
+        for counter in &[&self.multiplier, &self.score] {
+            if let Some(ref sprite) = counter.sprite {
+                let (w, h) = sprite.size();
+                right -= w;
+                phi.renderer.copy_sprite(sprite,
+                                         Rectangle {
+                                             x: right,
+                                             y: MARGIN,
+                                             w: w,
+                                             h: h,
+                                         });
+                right -= MARGIN;
+            }
+        }
+    }
+}
@@ -0,0 +1,660 @@
+use std::fs::File;
+use std::io::Read;
+use std::ops::Range;
+use std::path::Path;
+use toml_edit::{ImDocument, Item};
+use views::boss::BossDescr;
+use views::director::{EnemyKind, Ramp, ScheduledSpawn, Spawn, Wave, default_waves};
+use views::physics::{Edges, Physics, Well};
+
+/// The level played when starting a new game.
+pub const DEFAULT_LEVEL: &str = "assets/levels/asteroid_belt.toml";
+
+/// The numbers which define how the game plays, which designers may want to
+/// tweak for every level.
+#[derive(Clone, Debug)]
+pub struct Tuning {
+    /// Pixels traveled by the ship every second.
+    pub player_speed: f64,
+    /// Ships the player starts with.
+    pub player_lives: u32,
+    /// Seconds between the destruction of the ship and its respawn.
+    pub respawn_delay: f64,
+    /// Seconds during which a respawned ship cannot be hit.
+    pub invulnerability: f64,
+    /// Size at which asteroids of scale 1 are drawn, in pixels.
+    pub asteroid_side: f64,
+    /// Pixels traveled horizontally by a bullet every second.
+    pub bullet_speed: f64,
+    /// Chance, from 0 to 1, that a destroyed asteroid drops a power-up.
+    pub powerup_chance: f64,
+    /// Seconds the fire button must be held for a charge shot, which is
+    /// fired on release. 0 disables charge shots.
+    pub charge_time: f64,
+}
+
+impl Default for Tuning {
+    fn default() -> Tuning {
+        Tuning {
+            player_speed: 180.0,
+            player_lives: 3,
+            respawn_delay: 2.0,
+            invulnerability: 2.0,
+            asteroid_side: 96.0,
+            bullet_speed: 240.0,
+            powerup_chance: 0.1,
+            charge_time: 1.0,
+        }
+    }
+}
+
+/// The images of the three layers of the background, and how fast they
+/// scroll, in pixels per second.
+#[derive(Clone, Debug)]
+pub struct Backgrounds {
+    pub back: (String, f64),
+    pub middle: (String, f64),
+    pub front: (String, f64),
+}
+
+/// Everything which makes a level: what it looks and sounds like, how it
+/// plays, and what the player must face.
+#[derive(Clone, Debug)]
+pub struct Level {
+    pub name: String,
+    pub music: String,
+    /// If `None`, the level keeps the backgrounds of the previous view.
+    pub backgrounds: Option<Backgrounds>,
+    pub tuning: Tuning,
+    pub waves: Vec<Wave>,
+    pub spawns: Vec<ScheduledSpawn>,
+    /// The boss which ends the level, if any.
+    pub boss: Option<BossDescr>,
+    pub physics: Physics,
+}
+
+impl Default for Level {
+    fn default() -> Level {
+        Level {
+            name: "Asteroid Belt".to_string(),
+            music: "assets/mdk_phoenix_orchestral.ogg".to_string(),
+            backgrounds: None,
+            tuning: Tuning::default(),
+            waves: default_waves(),
+            spawns: vec![],
+            boss: None,
+            physics: Physics::default(),
+        }
+    }
+}
+
+/// The value of a key, as written in a level file.
+enum Value {
+    Number(f64),
+    Text(String),
+    /// `[from, to]`
+    Pair(f64, f64),
+}
+
+impl Value {
+    fn from_toml(value: &toml_edit::Value) -> Result<Value, String> {
+        match *value {
+            toml_edit::Value::String(ref text) => Ok(Value::Text(text.value().clone())),
+            toml_edit::Value::Array(ref array) => {
+                let numbers = array.iter().map(number).collect::<Result<Vec<f64>, _>>()?;
+                match numbers.len() {
+                    2 => Ok(Value::Pair(numbers[0], numbers[1])),
+                    n => Err(format!("expected `[from, to]`, found {} values", n)),
+                }
+            }
+            _ => number(value).map(Value::Number),
+        }
+    }
+
+    fn number(&self) -> Result<f64, String> {
+        match *self {
+            Value::Number(n) => Ok(n),
+            _ => Err("expected a number".to_string()),
+        }
+    }
+
+    fn positive(&self) -> Result<f64, String> {
+        let n = self.number()?;
+        if n > 0.0 {
+            Ok(n)
+        } else {
+            Err(format!("expected a positive number, found {}", n))
+        }
+    }
+
+    /// A number which may be 0 but not negative, such as a duration.
+    fn non_negative(&self) -> Result<f64, String> {
+        let n = self.number()?;
+        if n >= 0.0 {
+            Ok(n)
+        } else {
+            Err(format!("expected a number which is not negative, found {}", n))
+        }
+    }
+
+    /// A time, in seconds from the start of the level.
+    fn time(&self) -> Result<f64, String> {
+        let n = self.number()?;
+        if n >= 0.0 {
+            Ok(n)
+        } else {
+            Err(format!("expected a time, in seconds from the start, found {}", n))
+        }
+    }
+
+    /// A fraction of the screen.
+    fn fraction(&self) -> Result<f64, String> {
+        let n = self.number()?;
+        if (0.0..=1.0).contains(&n) {
+            Ok(n)
+        } else {
+            Err(format!("expected a fraction of the screen, in [0, 1], found {}", n))
+        }
+    }
+
+    fn text(&self) -> Result<String, String> {
+        match *self {
+            Value::Text(ref text) => Ok(text.clone()),
+            _ => Err("expected quoted text".to_string()),
+        }
+    }
+
+    /// A number is a ramp whose value never changes.
+    fn ramp(&self) -> Result<Ramp, String> {
+        match *self {
+            Value::Number(n) => Ok(Ramp::constant(n)),
+            Value::Pair(from, to) => Ok(Ramp { from: from, to: to }),
+            Value::Text(_) => Err("expected a number or `[from, to]`".to_string()),
+        }
+    }
+
+    /// A ramp which stays positive from start to end.
+    fn positive_ramp(&self) -> Result<Ramp, String> {
+        let ramp = self.ramp()?;
+        if ramp.from > 0.0 && ramp.to > 0.0 {
+            Ok(ramp)
+        } else {
+            Err(format!("expected positive numbers, found [{}, {}]", ramp.from, ramp.to))
+        }
+    }
+
+    fn enemy(&self) -> Result<EnemyKind, String> {
+        let name = self.text()?;
+        EnemyKind::from_name(&name).ok_or(format!("unknown enemy \"{}\"", name))
+    }
+}
+
+/// Reads a number, which must be finite: infinities and NaN would wreak
+/// havoc wherever they ended up.
+fn number(value: &toml_edit::Value) -> Result<f64, String> {
+    match *value {
+        toml_edit::Value::Integer(ref n) => Ok(*n.value() as f64),
+        toml_edit::Value::Float(ref n) if n.value().is_finite() => Ok(*n.value()),
+        toml_edit::Value::Float(ref n) => Err(format!("expected a finite number, found {}", n.value())),
+        _ => Err(format!("expected a number, found {}", value.type_name())),
+    }
+}
+
+/// The number of the line of `text` where `span` starts. Tables which are
+/// only implied by their subtables have no span, and are blamed on line 1.
+fn line_number(text: &str, span: Option<Range<usize>>) -> usize {
+    span.map_or(1, |span| text[..span.start].matches('\n').count() + 1)
+}
+
+/// The image and the speed of the back, middle and front layers of the
+/// background, as far as they have been read.
+type Layers = (Option<(String, f64)>, Option<(String, f64)>, Option<(String, f64)>);
+
+/// The part of the file which is being read.
+enum Section {
+    Level,
+    Backgrounds,
+    Tuning,
+    Boss,
+    Physics,
+    /// The last well of `Level::physics`.
+    Well,
+    /// The last wave of `Level::waves`.
+    Wave,
+    /// The last spawn of `Level::spawns`.
+    Spawn,
+}
+
+impl Level {
+    /// Reads a level file.
+    ///
+    /// Level files are TOML, with `[level]`, `[backgrounds]`, `[tuning]`,
+    /// `[boss]` and `[physics]` sections, and any number of `[[wave]]`,
+    /// `[[spawn]]` and `[[well]]` entries, each holding `key = value` lines.
+    /// Values are numbers, quoted text, or `[from, to]` for values which
+    /// change over the course of a wave. Anything left out keeps its default
+    /// value. See `assets/levels` for examples.
+    pub fn load(path: &Path) -> Result<Level, String> {
+        let mut text = String::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut text))
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        let at = |span: Option<Range<usize>>, error: String| {
+            format!("{}:{}: {}", path.display(), line_number(&text, span), error)
+        };
+        let document = ImDocument::parse(text.as_str())
+            .map_err(|e| at(e.span(), e.message().trim_end().replace('\n', "; ")))?;
+
+        let mut level = Level::default();
+        // Only the waves of the file are used, if it has any
+        level.waves.clear();
+        let mut backgrounds = (None, None, None);
+        for (name, item) in document.iter() {
+            let (header, tables) = match *item {
+                Item::Table(ref table) => (format!("[{}]", name), vec![table]),
+                Item::ArrayOfTables(ref array) => {
+                    (format!("[[{}]]", name), array.iter().collect())
+                }
+                _ => {
+                    return Err(at(item.span(),
+                                  format!("`{}`: expected a section, e.g. `[level]`", name)))
+                }
+            };
+            for table in tables {
+                let section = level.enter(&header).map_err(|e| at(table.span(), e))?;
+                for (key, item) in table.iter() {
+                    item.as_value()
+                        .ok_or(format!("`{}`: expected a value, found a {}", key, item.type_name()))
+                        .and_then(|value| {
+                            Value::from_toml(value).map_err(|e| format!("`{}`: {}", key, e))
+                        })
+                        .and_then(|value| {
+                            level.parse_key(&section, key, &value, &mut backgrounds)
+                        })
+                        .map_err(|e| at(item.span(), e))?;
+                }
+            }
+        }
+
+        if level.waves.is_empty() {
+            level.waves = default_waves();
+        }
+        level.backgrounds = match backgrounds {
+            (None, None, None) => None,
+            (Some(back), Some(middle), Some(front)) => {
+                if back.0.is_empty() || middle.0.is_empty() || front.0.is_empty() {
+                    return Err(format!("{}: [backgrounds] is missing the image of a layer",
+                                       path.display()));
+                }
+                Some(Backgrounds {
+                    back: back,
+                    middle: middle,
+                    front: front,
+                })
+            }
+            _ => {
+                return Err(format!("{}: [backgrounds] must set `back`, `middle` and `front`",
+                                   path.display()))
+            }
+        };
+        Ok(level)
+    }
+
+    /// Starts reading the section with the given `header`, adding an entry
+    /// for it if needed.
+    fn enter(&mut self, header: &str) -> Result<Section, String> {
+        Ok(match header {
+            "[level]" => Section::Level,
+            "[backgrounds]" => Section::Backgrounds,
+            "[tuning]" => Section::Tuning,
+            "[boss]" => {
+                self.boss = Some(BossDescr::default());
+                Section::Boss
+            }
+            "[physics]" => Section::Physics,
+            "[[well]]" => {
+                self.physics.wells.push(Well {
+                    pos: (0.5, 0.5),
+                    strength: 0.0,
+                });
+                Section::Well
+            }
+            "[[wave]]" => {
+                self.waves.push(Wave::default());
+                Section::Wave
+            }
+            "[[spawn]]" => {
+                self.spawns.push(ScheduledSpawn {
+                    time: 0.0,
+                    spawn: Spawn {
+                        enemy: EnemyKind::Asteroid,
+                        speed: 100.0,
+                        scale: 1.0,
+                        y: None,
+                    },
+                });
+                Section::Spawn
+            }
+            _ => return Err(format!("unknown section `{}`", header)),
+        })
+    }
+
+    fn parse_key(&mut self,
+                 section: &Section,
+                 key: &str,
+                 value: &Value,
+                 backgrounds: &mut Layers)
+                 -> Result<(), String> {
+        let unknown = || Err(format!("unknown key `{}`", key));
+
+        let result = match *section {
+            Section::Level => {
+                match key {
+                    "name" => value.text().map(|v| self.name = v),
+                    "music" => value.text().map(|v| self.music = v),
+                    _ => return unknown(),
+                }
+            }
+            Section::Backgrounds => {
+                let layer = match key {
+                    "back" | "back_speed" => &mut backgrounds.0,
+                    "middle" | "middle_speed" => &mut backgrounds.1,
+                    "front" | "front_speed" => &mut backgrounds.2,
+                    _ => return unknown(),
+                };
+                // Layers scroll at the default speed unless told otherwise
+                let default_speed = match key {
+                    "back" | "back_speed" => 20.0,
+                    "middle" | "middle_speed" => 40.0,
+                    _ => 80.0,
+                };
+                let layer = layer.get_or_insert((String::new(), default_speed));
+                if key.ends_with("_speed") {
+                    value.non_negative().map(|v| layer.1 = v)
+                } else {
+                    value.text().map(|v| layer.0 = v)
+                }
+            }
+            Section::Tuning => {
+                let tuning = &mut self.tuning;
+                match key {
+                    "player_speed" => value.positive().map(|v| tuning.player_speed = v),
+                    "player_lives" => {
+                        match value.number() {
+                            Ok(n) if n.round() >= 1.0 => {
+                                tuning.player_lives = n.round() as u32;
+                                Ok(())
+                            }
+                            Ok(_) => Err("the player needs at least 1 life".to_string()),
+                            Err(e) => Err(e),
+                        }
+                    }
+                    "respawn_delay" => value.non_negative().map(|v| tuning.respawn_delay = v),
+                    "invulnerability" => value.non_negative().map(|v| tuning.invulnerability = v),
+                    "asteroid_side" => value.positive().map(|v| tuning.asteroid_side = v),
+                    "bullet_speed" => value.positive().map(|v| tuning.bullet_speed = v),
+                    "charge_time" => value.non_negative().map(|v| tuning.charge_time = v),
+                    "powerup_chance" => {
+                        match value.number() {
+                            Ok(p) if (0.0..=1.0).contains(&p) => {
+                                tuning.powerup_chance = p;
+                                Ok(())
+                            }
+                            Ok(_) => Err("`powerup_chance` is a probability, in [0, 1]".to_string()),
+                            Err(e) => Err(e),
+                        }
+                    }
+                    _ => return unknown(),
+                }
+            }
+            Section::Boss => {
+                let boss = self.boss.as_mut().unwrap();
+                match key {
+                    "time" => value.time().map(|v| boss.time = v),
+                    "health" => value.positive().map(|v| boss.health = v),
+                    "turret_health" => value.positive().map(|v| boss.turret_health = v),
+                    _ => return unknown(),
+                }
+            }
+            Section::Physics => {
+                match key {
+                    "edges" => {
+                        let physics = &mut self.physics;
+                        value.text().and_then(|name| {
+                            Edges::from_name(&name)
+                                .map(|v| physics.edges = v)
+                                .ok_or(format!("unknown edges \"{}\"; expected \"cull\", \"bounce\" \
+                                                or \"wrap\"",
+                                               name))
+                        })
+                    }
+                    "lifetime" => value.positive().map(|v| self.physics.lifetime = v),
+                    _ => return unknown(),
+                }
+            }
+            Section::Well => {
+                let well = self.physics.wells.last_mut().unwrap();
+                match key {
+                    "x" => value.fraction().map(|v| well.pos.0 = v),
+                    "y" => value.fraction().map(|v| well.pos.1 = v),
+                    "strength" => value.number().map(|v| well.strength = v),
+                    _ => return unknown(),
+                }
+            }
+            Section::Wave => {
+                let wave = self.waves.last_mut().unwrap();
+                match key {
+                    "duration" => value.positive().map(|v| wave.duration = v),
+                    "enemy" => value.enemy().map(|v| wave.enemy = v),
+                    "rate" => value.positive_ramp().map(|v| wave.rate = v),
+                    "min_speed" => value.positive_ramp().map(|v| wave.min_speed = v),
+                    "max_speed" => value.positive_ramp().map(|v| wave.max_speed = v),
+                    "scale" => value.positive_ramp().map(|v| wave.scale = v),
+                    _ => return unknown(),
+                }
+            }
+            Section::Spawn => {
+                let scheduled = self.spawns.last_mut().unwrap();
+                match key {
+                    "time" => value.time().map(|v| scheduled.time = v),
+                    "enemy" => value.enemy().map(|v| scheduled.spawn.enemy = v),
+                    "speed" => value.positive().map(|v| scheduled.spawn.speed = v),
+                    "scale" => value.positive().map(|v| scheduled.spawn.scale = v),
+                    "y" => value.fraction().map(|v| scheduled.spawn.y = Some(v)),
+                    _ => return unknown(),
+                }
+            }
+        };
+        result.map_err(|e| format!("`{}`: {}", key, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// Writes a level file holding `body`, and returns its path. Every test
+    /// uses its own `name`, as tests run in parallel.
+    fn level_file(name: &str, body: &str) -> PathBuf {
+        let path = ::std::env::temp_dir()
+            .join(format!("arcaders-{}-{}.toml", name, ::std::process::id()));
+        fs::write(&path, body).unwrap();
+        path
+    }
+
+    fn load(name: &str, body: &str) -> Result<Level, String> {
+        let path = level_file(name, body);
+        let level = Level::load(&path);
+        fs::remove_file(&path).unwrap();
+        level
+    }
+
+    fn parse(text: &str) -> Result<Value, String> {
+        let value = text.parse::<toml_edit::Value>().map_err(|e| e.to_string())?;
+        Value::from_toml(&value)
+    }
+
+    #[test]
+    fn values_are_numbers_text_or_pairs() {
+        assert_eq!(parse("1.5").unwrap().number(), Ok(1.5));
+        assert_eq!(parse("-2").unwrap().number(), Ok(-2.0));
+        assert_eq!(parse("\"wrap\"").unwrap().text(), Ok("wrap".to_string()));
+        assert_eq!(parse("\"\"").unwrap().text(), Ok(String::new()));
+        let ramp = parse("[1, 2.5]").unwrap().ramp().unwrap();
+        assert_eq!((ramp.from, ramp.to), (1.0, 2.5));
+        let ramp = parse("3").unwrap().ramp().unwrap();
+        assert_eq!((ramp.from, ramp.to), (3.0, 3.0));
+        assert!(parse("[1, 2]").unwrap().number().is_err());
+        assert!(parse("1").unwrap().text().is_err());
+    }
+
+    #[test]
+    fn malformed_values_are_rejected() {
+        for text in &["wrap", "\"wrap", "\"", "[1, 2", "[1]", "[1, 2, 3]", "[1, a]", "nan", "inf",
+                      "-inf", "[0, nan]", "1e999", "true", "{ x = 1 }"] {
+            assert!(parse(text).is_err(), "`{}` should be rejected", text);
+        }
+    }
+
+    #[test]
+    fn values_are_checked_against_their_range() {
+        let value = |text| parse(text).unwrap();
+        assert!(value("0").positive().is_err());
+        assert!(value("0").time().is_ok());
+        assert!(value("0").non_negative().is_ok());
+        assert!(value("-0.5").non_negative().is_err());
+        assert!(value("-1").time().is_err());
+        assert!(value("1").fraction().is_ok());
+        assert!(value("1.5").fraction().is_err());
+        assert!(value("[1, 0]").positive_ramp().is_err());
+        assert!(value("[-1, 1]").positive_ramp().is_err());
+        assert!(value("[0.5, 2]").positive_ramp().is_ok());
+        assert_eq!(value("\"hunter\"").enemy(), Ok(EnemyKind::Hunter));
+        assert!(value("\"dragon\"").enemy().is_err());
+    }
+
+    #[test]
+    fn the_shipped_level_loads() {
+        let level = Level::load(Path::new(DEFAULT_LEVEL)).unwrap();
+        assert_eq!(level.name, "Asteroid Belt");
+        assert_eq!(level.waves.len(), 3);
+        assert!(!level.spawns.is_empty());
+        assert!(level.backgrounds.is_some());
+        assert!(level.boss.is_some());
+        assert_eq!(level.physics.edges, Edges::Bounce);
+    }
+
+    #[test]
+    fn values_left_out_keep_their_default() {
+        let level = load("defaults", "").unwrap();
+        assert_eq!(level.waves.len(), default_waves().len());
+        assert!(level.backgrounds.is_none());
+        assert!(level.boss.is_none());
+        assert_eq!(level.tuning.player_lives, Tuning::default().player_lives);
+
+        let level = load("partial", "[tuning]\nplayer_lives = 5\n[[wave]]\nenemy = \"fighter\"\n")
+            .unwrap();
+        assert_eq!(level.tuning.player_lives, 5);
+        assert_eq!(level.tuning.player_speed, Tuning::default().player_speed);
+        assert_eq!(level.waves.len(), 1);
+        assert_eq!(level.waves[0].enemy, EnemyKind::Fighter);
+        assert_eq!(level.waves[0].duration, Wave::default().duration);
+    }
+
+    #[test]
+    fn sections_and_entries_are_read() {
+        let level = load("entries",
+                         "[level]\n\
+                          name = \"Test\" \n\
+                          # A comment\n\
+                          [backgrounds]\n\
+                          back = \"a.png\"\n\
+                          middle = \"b.png\"\n\
+                          front = \"c.png\"\n\
+                          front_speed = 100\n\
+                          [boss]\n\
+                          health = 10\n\
+                          [physics]\n\
+                          edges = \"wrap\"\n\
+                          lifetime = 5\n\
+                          [[well]]\n\
+                          x = 0.25\n\
+                          strength = 1000\n\
+                          [[spawn]]\n\
+                          time = 3\n\
+                          enemy = \"swooper\"\n\
+                          y = 0.75\n")
+            .unwrap();
+        assert_eq!(level.name, "Test");
+        let backgrounds = level.backgrounds.unwrap();
+        assert_eq!(backgrounds.back, ("a.png".to_string(), 20.0));
+        assert_eq!(backgrounds.front, ("c.png".to_string(), 100.0));
+        let boss = level.boss.unwrap();
+        assert_eq!((boss.time, boss.health), (BossDescr::default().time, 10.0));
+        assert_eq!(level.physics.edges, Edges::Wrap);
+        assert_eq!(level.physics.lifetime, 5.0);
+        assert_eq!(level.physics.wells.len(), 1);
+        assert_eq!(level.physics.wells[0].pos, (0.25, 0.5));
+        assert_eq!(level.spawns.len(), 1);
+        assert_eq!(level.spawns[0].time, 3.0);
+        assert_eq!(level.spawns[0].spawn.enemy, EnemyKind::Swooper);
+        assert_eq!(level.spawns[0].spawn.y, Some(0.75));
+    }
+
+    #[test]
+    fn errors_point_at_their_line() {
+        let error = load("bad-lives", "[tuning]\n\nplayer_lives = 0.4\n").unwrap_err();
+        assert!(error.ends_with(":3: `player_lives`: the player needs at least 1 life"),
+                "{}",
+                error);
+        let error = load("bad-delay", "[tuning]\nplayer_lives = 2\nrespawn_delay = -1\n")
+            .unwrap_err();
+        assert!(error.ends_with(":3: `respawn_delay`: expected a number which is not negative, \
+                                 found -1"),
+                "{}",
+                error);
+        let error = load("bad-key", "[level]\nsong = \"a.ogg\"\n").unwrap_err();
+        assert!(error.ends_with(":2: unknown key `song`"), "{}", error);
+        let error = load("bad-section", "[levels]\n").unwrap_err();
+        assert!(error.ends_with(":1: unknown section `[levels]`"), "{}", error);
+        let error = load("no-section", "name = \"Test\"\n").unwrap_err();
+        assert!(error.ends_with(":1: `name`: expected a section, e.g. `[level]`"), "{}", error);
+    }
+
+    #[test]
+    fn invalid_levels_are_rejected() {
+        for &(name, body) in &[("no-value", "[level]\nname\n"),
+                               ("chance", "[tuning]\npowerup_chance = 2\n"),
+                               ("boss-time", "[boss]\ntime = -1\n"),
+                               ("wave-rate", "[[wave]]\nrate = [1, 0]\n"),
+                               ("wave-enemy", "[[wave]]\nenemy = asteroid\n"),
+                               ("edges", "[physics]\nedges = \"loop\"\n"),
+                               ("lifetime", "[physics]\nlifetime = 0\n"),
+                               ("respawn", "[tuning]\nrespawn_delay = -1\n"),
+                               ("invulnerability", "[tuning]\ninvulnerability = -2\n"),
+                               ("charge", "[tuning]\ncharge_time = -0.5\n"),
+                               ("nested", "[tuning.player]\nspeed = 1\n"),
+                               ("well", "[[well]]\ny = 2\n"),
+                               ("layers", "[backgrounds]\nback = \"a.png\"\n"),
+                               ("layer-image",
+                                "[backgrounds]\nback = \"a.png\"\nmiddle = \"b.png\"\n\
+                                 front_speed = 1\n"),
+                               ("layer-speed",
+                                "[backgrounds]\nback = \"a.png\"\nmiddle = \"b.png\"\n\
+                                 front = \"c.png\"\nmiddle_speed = -40\n")] {
+            assert!(load(name, body).is_err(), "{} should be rejected", name);
+        }
+    }
+
+    #[test]
+    fn syntax_errors_point_at_their_line() {
+        let error = load("syntax", "[level]\nname = \"Test\"\nmusic = a.ogg\n").unwrap_err();
+        assert!(error.contains(":3: "), "{}", error);
+        let error = load("duplicate", "[tuning]\nplayer_lives = 2\nplayer_lives = 3\n")
+            .unwrap_err();
+        assert!(error.contains(":3: "), "{}", error);
+        assert!(Level::load(Path::new("assets/levels/missing.toml")).is_err());
+    }
+}
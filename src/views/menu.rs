@@ -0,0 +1,204 @@
+use phi::{Phi, ViewAction};
+use phi::gfx::{CopySprite, Sprite};
+use phi::data::Rectangle;
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+use views::shared::BgSet;
+
+const FONT_SIZE: i32 = 32;
+const LABEL_H: f64 = 50.0;
+
+/// What an action does once chosen, given the backgrounds of the menu.
+pub type ActionFn = Box<dyn Fn(&mut Phi, BgSet) -> ViewAction>;
+
+pub struct Action {
+    /// The function which should be executed if the action is chosen.
+    func: ActionFn,
+    font_size: i32,
+    idle_sprite: Sprite,
+    hover_sprite: Sprite,
+}
+
+impl Action {
+    pub fn new(phi: &mut Phi,
+               label: &str,
+               func: ActionFn)
+               -> Action {
+        Action::with_font_size(phi, label, FONT_SIZE, func)
+    }
+
+    pub fn with_font_size(phi: &mut Phi,
+                          label: &str,
+                          font_size: i32,
+                          func: ActionFn)
+                          -> Action {
+        let (idle_sprite, hover_sprite) = Action::label_sprites(phi, label, font_size);
+        Action {
+            func: func,
+            font_size: font_size,
+            idle_sprite: idle_sprite,
+            hover_sprite: hover_sprite,
+        }
+    }
+
+    /// Changes the text displayed for this action, e.g. to show a setting's
+    /// current value.
+    pub fn set_label(&mut self, phi: &mut Phi, label: &str) {
+        let (idle_sprite, hover_sprite) = Action::label_sprites(phi, label, self.font_size);
+        self.idle_sprite = idle_sprite;
+        self.hover_sprite = hover_sprite;
+    }
+
+    fn label_sprites(phi: &mut Phi, label: &str, font_size: i32) -> (Sprite, Sprite) {
+        (phi.ttf_str_sprite(label, "assets/belligerent.ttf", font_size, Color::RGB(255, 255, 255))
+             .unwrap(),
+         phi.ttf_str_sprite(label,
+                            "assets/belligerent.ttf",
+                            font_size * 6 / 5,
+                            Color::RGB(216, 21, 21))
+             .unwrap())
+    }
+}
+
+/// A vertical list of actions inside a colored box, navigated with the arrow
+/// keys and activated with Space or Enter.
+pub struct Menu {
+    pub actions: Vec<Action>,
+    pub selected: i8,
+    /// Vertical space given to every action, in pixels.
+    label_h: f64,
+}
+
+impl Menu {
+    pub fn new(actions: Vec<Action>) -> Menu {
+        Menu::with_label_height(actions, LABEL_H)
+    }
+
+    /// Builds a menu whose actions are closer to each other than usual, e.g.
+    /// because they use a smaller font.
+    pub fn with_label_height(actions: Vec<Action>, label_h: f64) -> Menu {
+        Menu {
+            actions: actions,
+            selected: 0,
+            label_h: label_h,
+        }
+    }
+
+    /// Moves the selection according to the player's input. If an action was
+    /// chosen, returns its index along with the `ViewAction` it produced.
+    ///
+    /// Actions may be chosen with the keyboard, or by clicking them. Hovering
+    /// an action with the mouse selects it.
+    pub fn update(&mut self, phi: &mut Phi, bg: &BgSet) -> Option<(usize, ViewAction)> {
+        let layout = self.layout(phi);
+        if phi.events.now.mouse_moved {
+            let mouse = phi.events.mouse;
+            if let Some(i) = layout.rows.iter().position(|row| row.contains_point(mouse)) {
+                self.selected = i as i8;
+            }
+        }
+        if let Some(click) = phi.events.now.click {
+            if let Some(i) = layout.rows.iter().position(|row| row.contains_point(click)) {
+                self.selected = i as i8;
+                return Some((i, (self.actions[i].func)(phi, bg.clone())));
+            }
+        }
+
+        if phi.events.now.key_space == Some(true) || phi.events.now.key_enter == Some(true) {
+            let selected = self.selected as usize;
+            return Some((selected, (self.actions[selected].func)(phi, bg.clone())));
+        }
+        // Scrolling the wheel away from the user goes up
+        if phi.events.now.key_up == Some(true) || phi.events.now.wheel.1 > 0 {
+            self.selected -= 1;
+            if self.selected < 0 {
+                self.selected = self.actions.len() as i8 - 1;
+            }
+        }
+        if phi.events.now.key_down == Some(true) || phi.events.now.wheel.1 < 0 {
+            self.selected += 1;
+            if self.selected >= self.actions.len() as i8 {
+                self.selected = 0;
+            }
+        }
+        None
+    }
+
+    /// Computes where the box and every action are drawn.
+    fn layout(&self, phi: &Phi) -> MenuLayout {
+        let (win_w, win_h) = phi.output_size();
+        let label_h = self.label_h;
+        // Wide enough for the longest label, with some room on both sides
+        let box_w = self.actions
+            .iter()
+            .map(|action| action.hover_sprite.size().0 + 40.0)
+            .fold(360.0, f64::max);
+        let box_h = self.actions.len() as f64 * label_h;
+        let margin_h = 10.0;
+
+        MenuLayout {
+            inner: Rectangle {
+                w: box_w,
+                h: box_h + margin_h * 2.0,
+                x: (win_w - box_w) / 2.0,
+                y: (win_h - box_h) / 2.0 - margin_h,
+            },
+            //? We place every element under the previous one.
+            rows: (0..self.actions.len())
+                .map(|i| {
+                         Rectangle {
+                             w: box_w,
+                             h: label_h,
+                             x: (win_w - box_w) / 2.0,
+                             y: (win_h - box_h) / 2.0 + label_h * i as f64,
+                         }
+                     })
+                .collect(),
+        }
+    }
+
+    pub fn render(&self, phi: &mut Phi) {
+        let layout = self.layout(phi);
+        let border_width = 3.0;
+        // Render the border of the colored box which holds the labels
+        phi.renderer.set_draw_color(Color::RGB(70, 15, 70));
+        let _ = phi.renderer.fill_rect(Rectangle {
+                                               w: layout.inner.w + border_width * 2.0,
+                                               h: layout.inner.h + border_width * 2.0,
+                                               x: layout.inner.x - border_width,
+                                               y: layout.inner.y - border_width,
+                                           }
+                                           .to_sdl()
+                                           .unwrap());
+        // Render the colored box which holds the labels
+        phi.renderer.set_draw_color(Color::RGB(140, 30, 140));
+        let _ = phi.renderer.fill_rect(layout.inner.to_sdl().unwrap());
+        for (i, (action, row)) in self.actions.iter().zip(layout.rows).enumerate() {
+            let sprite = if self.selected as usize == i {
+                &action.hover_sprite
+            } else {
+                &action.idle_sprite
+            };
+            let (w, h) = sprite.size();
+            phi.renderer.copy_sprite(sprite, Rectangle::with_size(w, h).center_at(row.center()));
+        }
+    }
+}
+
+/// Where the parts of a menu are drawn on the screen.
+struct MenuLayout {
+    /// The colored box, without its border.
+    inner: Rectangle,
+    /// The area taken by every action.
+    rows: Vec<Rectangle>,
+}
+
+/// Darkens whatever has been drawn so far, so that an overlay stands out from
+/// the views underneath.
+pub fn dim_screen(phi: &mut Phi) {
+    let (win_w, win_h) = phi.output_size();
+    phi.renderer.set_blend_mode(BlendMode::Blend);
+    phi.renderer.set_draw_color(Color::RGBA(0, 0, 0, 160));
+    let _ = phi.renderer.fill_rect(Rectangle::with_size(win_w, win_h).to_sdl().unwrap());
+    phi.renderer.set_blend_mode(BlendMode::None);
+}
@@ -1,4 +1,21 @@
+pub mod controls;
+pub mod demo;
+pub mod director;
+pub mod enemies;
 pub mod game;
+pub mod game_over;
+pub mod high_scores;
+pub mod hud;
+pub mod level;
 pub mod main_menu;
+pub mod menu;
+pub mod name_entry;
+pub mod options;
+pub mod physics;
+pub mod pause;
+pub mod score;
 pub mod shared;
+pub mod boss;
 pub mod bullets;
+pub mod powerups;
+pub mod weapons;
@@ -0,0 +1,150 @@
+use phi::{Phi, View, ViewAction};
+use phi::gfx::{CopySprite, Sprite};
+use phi::data::Rectangle;
+use sdl2::pixels::Color;
+use views::high_scores::{HighScores, HighScoresView};
+use views::shared::BgSet;
+
+/// Characters the player may pick from, in order.
+const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 ";
+const NAME_LEN: usize = 3;
+const LETTER_SIZE: i32 = 64;
+const LETTER_W: f64 = 60.0;
+
+/// Lets the player enter their initials, arcade style, after a game whose
+/// score made it into the high score table. Up and down change the current
+/// letter, left and right move between letters, and Enter confirms.
+pub struct NameEntryView {
+    score: u32,
+    /// Index in `ALPHABET` of every letter of the name.
+    letters: [usize; NAME_LEN],
+    cursor: usize,
+    title: Sprite,
+    score_sprite: Sprite,
+    /// The idle and the selected sprite of every character of `ALPHABET`.
+    glyphs: Vec<(Sprite, Sprite)>,
+    bg: BgSet,
+}
+
+impl NameEntryView {
+    pub fn new(phi: &mut Phi, bg: BgSet, score: u32) -> NameEntryView {
+        let glyphs = ALPHABET.iter()
+            .map(|&c| {
+                // TTF cannot render an empty string, so show spaces as dots
+                let text = if c == b' ' {
+                    ".".to_string()
+                } else {
+                    (c as char).to_string()
+                };
+                (phi.ttf_str_sprite(&text, "assets/belligerent.ttf", LETTER_SIZE, Color::RGB(255, 255, 255))
+                     .unwrap(),
+                 phi.ttf_str_sprite(&text, "assets/belligerent.ttf", LETTER_SIZE, Color::RGB(216, 21, 21))
+                     .unwrap())
+            })
+            .collect();
+
+        NameEntryView {
+            score: score,
+            letters: [0; NAME_LEN],
+            cursor: 0,
+            title: phi.ttf_str_sprite("New High Score!",
+                                 "assets/belligerent.ttf",
+                                 48,
+                                 Color::RGB(216, 21, 21))
+                .unwrap(),
+            score_sprite: phi.ttf_str_sprite(&format!("Score: {}", score),
+                                        "assets/belligerent.ttf",
+                                        32,
+                                        Color::RGB(255, 255, 255))
+                .unwrap(),
+            glyphs: glyphs,
+            bg: bg,
+        }
+    }
+
+    fn name(&self) -> String {
+        let name: String = self.letters.iter().map(|&i| ALPHABET[i] as char).collect();
+        match name.trim() {
+            "" => "???".to_string(),
+            name => name.to_string(),
+        }
+    }
+}
+
+impl View for NameEntryView {
+    fn update(&mut self, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        if phi.events.now.key_enter == Some(true) {
+            let mut scores = HighScores::load();
+            let rank = scores.insert(&self.name(), self.score);
+            if let Err(e) = scores.save() {
+                println!("Could not save the high scores: {}", e);
+            }
+            let bg = self.bg.clone();
+            return ViewAction::Replace(Box::new(HighScoresView::new(phi, bg, rank)));
+        }
+
+        let letter = &mut self.letters[self.cursor];
+        if phi.events.now.key_up == Some(true) {
+            *letter = (*letter + 1) % ALPHABET.len();
+        }
+        if phi.events.now.key_down == Some(true) {
+            *letter = (*letter + ALPHABET.len() - 1) % ALPHABET.len();
+        }
+        if phi.events.now.key_right == Some(true) && self.cursor + 1 < NAME_LEN {
+            self.cursor += 1;
+        }
+        if phi.events.now.key_left == Some(true) && self.cursor > 0 {
+            self.cursor -= 1;
+        }
+
+        self.bg.update(elapsed);
+        ViewAction::None
+    }
+
+    fn draw(&self, phi: &mut Phi, alpha: f64) {
+        // Clear the screen
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+        // Render the background
+        self.bg.back.render(&mut phi.renderer, alpha);
+        self.bg.middle.render(&mut phi.renderer, alpha);
+        self.bg.front.render(&mut phi.renderer, alpha);
+
+        let (win_w, win_h) = phi.output_size();
+        let (title_w, title_h) = self.title.size();
+        phi.renderer.copy_sprite(&self.title,
+                                 Rectangle {
+                                     x: (win_w - title_w) / 2.0,
+                                     y: win_h / 6.0,
+                                     w: title_w,
+                                     h: title_h,
+                                 });
+        let (score_w, score_h) = self.score_sprite.size();
+        phi.renderer.copy_sprite(&self.score_sprite,
+                                 Rectangle {
+                                     x: (win_w - score_w) / 2.0,
+                                     y: win_h / 6.0 + title_h + 10.0,
+                                     w: score_w,
+                                     h: score_h,
+                                 });
+
+        // Render every letter centered in its own slot, the current one in red
+        let left = (win_w - LETTER_W * NAME_LEN as f64) / 2.0;
+        for (i, &letter) in self.letters.iter().enumerate() {
+            let (ref idle, ref hover) = self.glyphs[letter];
+            let sprite = if i == self.cursor { hover } else { idle };
+            let (w, h) = sprite.size();
+            phi.renderer.copy_sprite(sprite,
+                                     Rectangle {
+                                         x: left + LETTER_W * i as f64 + (LETTER_W - w) / 2.0,
+                                         y: (win_h - h) / 2.0,
+                                         w: w,
+                                         h: h,
+                                     });
+        }
+    }
+}
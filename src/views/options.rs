@@ -0,0 +1,94 @@
+use phi::{Phi, View, ViewAction};
+use sdl2::mixer::{Channel, Music, MAX_VOLUME};
+use views::menu::{dim_screen, Action, Menu};
+use views::shared::BgSet;
+
+/// Amount by which a volume changes every time its action is chosen.
+const VOLUME_STEP: i32 = MAX_VOLUME / 4;
+
+const MUSIC: usize = 0;
+const SOUNDS: usize = 1;
+
+/// Drawn over whichever view pushed it, and popped when done.
+pub struct OptionsView {
+    menu: Menu,
+    bg: BgSet,
+}
+
+impl OptionsView {
+    pub fn new(phi: &mut Phi, bg: BgSet) -> OptionsView {
+        OptionsView {
+            menu: Menu::new(vec![Action::new(phi,
+                                             &music_label(),
+                                             Box::new(|_, _| {
+                                                          Music::set_volume(next_volume(Music::get_volume()));
+                                                          ViewAction::None
+                                                      })),
+                                 Action::new(phi,
+                                             &sounds_label(),
+                                             Box::new(|_, _| {
+                                                          let volume = Channel::all().get_volume();
+                                                          Channel::all().set_volume(next_volume(volume));
+                                                          ViewAction::None
+                                                      })),
+                                 Action::new(phi,
+                                             "Controls",
+                                             Box::new(|phi, bg| {
+                                                          ViewAction::Push(Box::new(
+                        ::views::controls::ControlsView::new(phi, bg)))
+                                                      })),
+                                 Action::new(phi, "Back", Box::new(|_, _| ViewAction::Pop))]),
+            bg: bg,
+        }
+    }
+}
+
+impl View for OptionsView {
+    fn update(&mut self, phi: &mut Phi, _elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+        if phi.events.now.key_escape == Some(true) {
+            return ViewAction::Pop;
+        }
+
+        match self.menu.update(phi, &self.bg) {
+            Some((MUSIC, action)) => {
+                self.menu.actions[MUSIC].set_label(phi, &music_label());
+                action
+            }
+            Some((SOUNDS, action)) => {
+                self.menu.actions[SOUNDS].set_label(phi, &sounds_label());
+                action
+            }
+            Some((_, action)) => action,
+            None => ViewAction::None,
+        }
+    }
+
+    fn draw(&self, phi: &mut Phi, _alpha: f64) {
+        dim_screen(phi);
+        self.menu.render(phi);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
+
+/// Cycles through the volumes, going back to silence after the loudest one.
+fn next_volume(volume: i32) -> i32 {
+    if volume >= MAX_VOLUME {
+        0
+    } else {
+        ::std::cmp::min(volume + VOLUME_STEP, MAX_VOLUME)
+    }
+}
+
+fn music_label() -> String {
+    format!("Music {}%", Music::get_volume() * 100 / MAX_VOLUME)
+}
+
+fn sounds_label() -> String {
+    format!("Sounds {}%", Channel::all().get_volume() * 100 / MAX_VOLUME)
+}
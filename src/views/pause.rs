@@ -0,0 +1,72 @@
+use phi::{Phi, View, ViewAction};
+use sdl2::mixer::Music;
+use views::menu::{dim_screen, Action, Menu};
+use views::shared::BgSet;
+
+/// Drawn over a `GameView`, which stays frozen underneath until the player
+/// resumes.
+pub struct PauseView {
+    menu: Menu,
+    bg: BgSet,
+}
+
+impl PauseView {
+    /// `bg` is the game's background set, used to build the next view.
+    pub fn new(phi: &mut Phi, bg: BgSet) -> PauseView {
+        PauseView {
+            menu: Menu::new(vec![Action::new(phi, "Resume", Box::new(|_, _| ViewAction::Pop)),
+                                 Action::new(phi,
+                                             "Restart",
+                                             Box::new(|phi, bg| {
+                                                          ViewAction::Reset(Box::new(
+                        ::views::game::GameView::with_backgrounds(phi, bg)))
+                                                      })),
+                                 Action::new(phi,
+                                             "Options",
+                                             Box::new(|phi, bg| {
+                                                          ViewAction::Push(Box::new(
+                        ::views::options::OptionsView::new(phi, bg)))
+                                                      })),
+                                 Action::new(phi,
+                                             "Quit to Menu",
+                                             Box::new(|phi, bg| {
+                                                          ViewAction::Reset(Box::new(
+                        ::views::main_menu::MainMenuView::with_backgrounds(phi, bg)))
+                                                      }))]),
+            bg: bg,
+        }
+    }
+}
+
+impl View for PauseView {
+    fn enter(&mut self, _phi: &mut Phi) {
+        Music::pause();
+    }
+
+    fn exit(&mut self, _phi: &mut Phi) {
+        Music::resume();
+    }
+
+    fn update(&mut self, phi: &mut Phi, _elapsed: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+        if phi.events.now.key_escape == Some(true) {
+            return ViewAction::Pop;
+        }
+
+        match self.menu.update(phi, &self.bg) {
+            Some((_, action)) => action,
+            None => ViewAction::None,
+        }
+    }
+
+    fn draw(&self, phi: &mut Phi, _alpha: f64) {
+        dim_screen(phi);
+        self.menu.render(phi);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,137 @@
+use phi::Phi;
+use phi::data::Rectangle;
+use sdl2::pixels::Color;
+
+/// Wells never pull harder than they would at this distance, in pixels, so
+/// that objects passing through them are not flung away.
+const MIN_WELL_DISTANCE: f64 = 40.0;
+const WELL_SIDE: f64 = 8.0;
+/// Default for `Physics::lifetime`, in seconds.
+const LIFETIME: f64 = 30.0;
+
+/// What happens to objects which reach the edges of the screen.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Edges {
+    /// They vanish once they have left the screen.
+    Cull,
+    /// They bounce off the top and bottom edges, and vanish once they have
+    /// left the screen through the left or the right.
+    Bounce,
+    /// They come back through the opposite edge, as in an arena, until they
+    /// get older than `Physics::lifetime`.
+    Wrap,
+}
+
+impl Edges {
+    pub fn from_name(name: &str) -> Option<Edges> {
+        match name {
+            "cull" => Some(Edges::Cull),
+            "bounce" => Some(Edges::Bounce),
+            "wrap" => Some(Edges::Wrap),
+            _ => None,
+        }
+    }
+}
+
+/// A point which attracts the objects around it.
+#[derive(Clone, Debug)]
+pub struct Well {
+    /// As a fraction of the width and the height of the screen.
+    pub pos: (f64, f64),
+    /// The acceleration at a distance of one pixel, in pixels per second
+    /// squared. It decreases with the square of the distance.
+    pub strength: f64,
+}
+
+/// How asteroids and power-ups move on their own, as set by a level.
+#[derive(Clone, Debug)]
+pub struct Physics {
+    pub edges: Edges,
+    pub wells: Vec<Well>,
+    /// Seconds after which objects stop wrapping around, and vanish once
+    /// they leave the screen, lest they pile up forever.
+    pub lifetime: f64,
+}
+
+impl Default for Physics {
+    fn default() -> Physics {
+        Physics {
+            edges: Edges::Cull,
+            wells: vec![],
+            lifetime: LIFETIME,
+        }
+    }
+}
+
+impl Physics {
+    /// Moves an object by its velocity, in pixels per second, after the wells
+    /// have pulled on it. `screen` is the size of the screen, and `age` is
+    /// how many seconds ago the object appeared. Returns `false` once the
+    /// object should be removed.
+    pub fn step(&self,
+                screen: (f64, f64),
+                rect: &mut Rectangle,
+                vel: &mut (f64, f64),
+                age: f64,
+                dt: f64)
+                -> bool {
+        let center = rect.center();
+        for well in &self.wells {
+            let (dx, dy) = (well.pos.0 * screen.0 - center.0, well.pos.1 * screen.1 - center.1);
+            let distance = (dx * dx + dy * dy).sqrt().max(MIN_WELL_DISTANCE);
+            let acceleration = well.strength / (distance * distance);
+            vel.0 += dx / distance * acceleration * dt;
+            vel.1 += dy / distance * acceleration * dt;
+        }
+        rect.x += vel.0 * dt;
+        rect.y += vel.1 * dt;
+
+        let (w, h) = screen;
+        match self.edges {
+            Edges::Wrap if age > self.lifetime => !has_left(screen, *rect, *vel),
+            Edges::Wrap => {
+                // Wrap once the object has completely left the screen
+                if rect.x + rect.w < 0.0 {
+                    rect.x += w + rect.w;
+                } else if rect.x > w {
+                    rect.x -= w + rect.w;
+                }
+                if rect.y + rect.h < 0.0 {
+                    rect.y += h + rect.h;
+                } else if rect.y > h {
+                    rect.y -= h + rect.h;
+                }
+                true
+            }
+            Edges::Bounce => {
+                if rect.y < 0.0 {
+                    rect.y = -rect.y;
+                    vel.1 = vel.1.abs();
+                } else if rect.y + rect.h > h {
+                    rect.y = 2.0 * (h - rect.h) - rect.y;
+                    vel.1 = -vel.1.abs();
+                }
+                !has_left(screen, *rect, *vel)
+            }
+            Edges::Cull => !has_left(screen, *rect, *vel),
+        }
+    }
+
+    /// Shows where the wells are.
+    pub fn render(&self, phi: &mut Phi) {
+        let (w, h) = phi.output_size();
+        phi.renderer.set_draw_color(Color::RGB(120, 60, 160));
+        for well in &self.wells {
+            let marker = Rectangle::with_size(WELL_SIDE, WELL_SIDE)
+                .center_at((well.pos.0 * w, well.pos.1 * h));
+            let _ = phi.renderer.draw_rect(marker.to_sdl().unwrap());
+        }
+    }
+}
+
+/// Whether an object has left the screen and is moving away from it. Objects
+/// enter from the right, so they may be beyond it while moving left.
+fn has_left(screen: (f64, f64), rect: Rectangle, vel: (f64, f64)) -> bool {
+    rect.x + rect.w <= 0.0 || (rect.x >= screen.0 && vel.0 >= 0.0) ||
+    (rect.y + rect.h <= 0.0 && vel.1 <= 0.0) || (rect.y >= screen.1 && vel.1 >= 0.0)
+}
@@ -0,0 +1,183 @@
+use phi::Phi;
+use phi::data::Rectangle;
+use phi::gfx::{AnimatedSprite, CopySprite, Sprite};
+use rand::Rng;
+use sdl2::pixels::Color;
+use views::physics::Physics;
+
+const FONT_PATH: &str = "assets/belligerent.ttf";
+const FONT_SIZE: i32 = 28;
+/// Size of the hitbox of a power-up, in pixels.
+const POWERUP_SIDE: f64 = 28.0;
+/// Power-ups pulse through these many shades of their color, and back.
+const PULSE_FRAMES: usize = 6;
+const PULSE_FPS: f64 = 12.0;
+/// Power-ups drift slower than the asteroid they came from, so that the
+/// player has time to catch them.
+const DRIFT_RATIO: f64 = 0.5;
+const MIN_DRIFT: f64 = 40.0;
+
+/// Seconds during which the ship cannot be hit after picking up a shield.
+pub const SHIELD_DURATION: f64 = 8.0;
+/// Seconds during which the ship moves faster after picking up a speed
+/// boost, and by how much.
+pub const BOOST_DURATION: f64 = 8.0;
+pub const BOOST_FACTOR: f64 = 1.5;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PowerUpKind {
+    /// Raises the power level of the weapon.
+    Weapon,
+    /// Protects the ship for a while.
+    Shield,
+    ExtraLife,
+    /// Makes the ship faster for a while.
+    Speed,
+    /// Destroys everything on the screen.
+    Bomb,
+}
+
+impl PowerUpKind {
+    /// Every kind, along with how likely it is to drop compared to the
+    /// others.
+    fn weighted() -> [(PowerUpKind, u32); 5] {
+        [(PowerUpKind::Weapon, 35),
+         (PowerUpKind::Shield, 20),
+         (PowerUpKind::Speed, 20),
+         (PowerUpKind::Bomb, 15),
+         (PowerUpKind::ExtraLife, 10)]
+    }
+
+    fn label(&self) -> &'static str {
+        match *self {
+            PowerUpKind::Weapon => "W",
+            PowerUpKind::Shield => "S",
+            PowerUpKind::ExtraLife => "1UP",
+            PowerUpKind::Speed => "V",
+            PowerUpKind::Bomb => "B",
+        }
+    }
+
+    fn color(&self) -> (u8, u8, u8) {
+        match *self {
+            PowerUpKind::Weapon => (230, 230, 30),
+            PowerUpKind::Shield => (60, 170, 255),
+            PowerUpKind::ExtraLife => (60, 230, 60),
+            PowerUpKind::Speed => (230, 120, 30),
+            PowerUpKind::Bomb => (230, 50, 30),
+        }
+    }
+}
+
+/// A pickup which drifts left until the player collects it.
+pub struct PowerUp {
+    kind: PowerUpKind,
+    rect: Rectangle,
+    /// Pixels traveled every second, horizontally and vertically.
+    vel: (f64, f64),
+    sprite: AnimatedSprite,
+    /// Size of the label drawn by `sprite`.
+    label: (f64, f64),
+    /// Seconds since the power-up appeared.
+    age: f64,
+}
+
+impl PowerUp {
+    /// Returns `None` once the power-up has left the screen.
+    pub fn update(mut self, physics: &Physics, screen: (f64, f64), dt: f64) -> Option<PowerUp> {
+        self.sprite.add_time(dt);
+        self.age += dt;
+        if physics.step(screen, &mut self.rect, &mut self.vel, self.age, dt) {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    pub fn render(&self, phi: &mut Phi) {
+        let (r, g, b) = self.kind.color();
+        phi.renderer.set_draw_color(Color::RGB(r, g, b));
+        let _ = phi.renderer.draw_rect(self.rect.to_sdl().unwrap());
+
+        // The label keeps its proportions, centered in its box
+        let (w, h) = self.label;
+        let scale = (self.rect.w / w).min(self.rect.h / h);
+        let dest = Rectangle::with_size(w * scale, h * scale).center_at(self.rect.center());
+        phi.renderer.copy_sprite(&self.sprite, dest);
+    }
+
+    pub fn kind(&self) -> PowerUpKind {
+        self.kind
+    }
+
+    pub fn rect(&self) -> Rectangle {
+        self.rect
+    }
+}
+
+/// Decides whether destroyed asteroids leave a power-up behind, and which.
+pub struct PowerUpFactory {
+    /// The animation of every kind, and the size of its frames.
+    sprites: Vec<(PowerUpKind, AnimatedSprite, (f64, f64))>,
+    /// From 0 to 1.
+    drop_chance: f64,
+}
+
+impl PowerUpFactory {
+    pub fn new(phi: &mut Phi, drop_chance: f64) -> PowerUpFactory {
+        let sprites = PowerUpKind::weighted()
+            .iter()
+            .map(|&(kind, _)| {
+                // Pulse from dim to bright, and back
+                let (r, g, b) = kind.color();
+                let frames: Vec<Sprite> = (0..PULSE_FRAMES)
+                    .chain((1..PULSE_FRAMES - 1).rev())
+                    .map(|i| {
+                        let light = 0.4 + 0.6 * i as f64 / (PULSE_FRAMES - 1) as f64;
+                        let shade = |c: u8| (c as f64 * light) as u8;
+                        let color = Color::RGB(shade(r), shade(g), shade(b));
+                        phi.ttf_str_sprite(kind.label(), FONT_PATH, FONT_SIZE, color).unwrap()
+                    })
+                    .collect();
+                // Every frame has the same size, as they only differ in color
+                let size = frames[0].size();
+                (kind, AnimatedSprite::with_fps(frames, PULSE_FPS), size)
+            })
+            .collect();
+
+        PowerUpFactory {
+            sprites: sprites,
+            drop_chance: drop_chance,
+        }
+    }
+
+    /// Rolls for a power-up where an asteroid moving at `speed` pixels per
+    /// second was destroyed.
+    pub fn maybe_drop(&self, phi: &mut Phi, center: (f64, f64), speed: f64) -> Option<PowerUp> {
+        if phi.rng.gen_range(0.0, 1.0) >= self.drop_chance {
+            return None;
+        }
+
+        let weighted = PowerUpKind::weighted();
+        let total = weighted.iter().map(|&(_, weight)| weight).sum::<u32>();
+        let mut roll = phi.rng.gen_range(0, total);
+        let mut kind = weighted[0].0;
+        for &(candidate, weight) in weighted.iter() {
+            if roll < weight {
+                kind = candidate;
+                break;
+            }
+            roll -= weight;
+        }
+
+        let &(_, ref sprite, label) = self.sprites.iter().find(|s| s.0 == kind).unwrap();
+        Some(PowerUp {
+            kind: kind,
+            rect: Rectangle::with_size(POWERUP_SIDE, POWERUP_SIDE).center_at(center),
+            vel: (-(speed * DRIFT_RATIO).max(MIN_DRIFT), 0.0),
+            sprite: sprite.clone(),
+            label: label,
+            age: 0.0,
+        })
+    }
+}
@@ -0,0 +1,181 @@
+/// Points awarded for an asteroid of reference size and speed.
+const ASTEROID_POINTS: f64 = 100.0;
+/// Size of an asteroid worth exactly `ASTEROID_POINTS`, in pixels.
+const REFERENCE_SIDE: f64 = 96.0;
+/// Asteroids smaller than this, in pixels, are worth as much as if they were
+/// this size, lest tiny asteroids be worth an unbounded number of points.
+const MIN_SIDE: f64 = 8.0;
+/// Speed of an asteroid worth exactly `ASTEROID_POINTS`, in pixels per second.
+const REFERENCE_SPEED: f64 = 100.0;
+/// Points awarded for an enemy ship.
+const ENEMY_POINTS: u32 = 250;
+/// Points awarded for destroying a boss, on top of its turrets.
+const BOSS_POINTS: u32 = 5000;
+/// Seconds the player has to destroy another asteroid to keep a chain going.
+const CHAIN_WINDOW: f64 = 1.5;
+/// Asteroids to destroy within a chain to increase the multiplier by one.
+const KILLS_PER_MULTIPLIER: u32 = 3;
+const MAX_MULTIPLIER: u32 = 8;
+/// Seconds the player must survive without getting hit to earn a bonus.
+const NO_HIT_INTERVAL: f64 = 30.0;
+const NO_HIT_BONUS: u32 = 1000;
+
+/// Keeps track of the player's score over a game.
+pub struct Score {
+    points: u32,
+    /// Asteroids destroyed in the current chain.
+    chain: u32,
+    /// Seconds left to extend the current chain.
+    chain_left: f64,
+    /// Seconds since the player was last hit.
+    since_hit: f64,
+}
+
+impl Default for Score {
+    fn default() -> Score {
+        Score::new()
+    }
+}
+
+impl Score {
+    pub fn new() -> Score {
+        Score {
+            points: 0,
+            chain: 0,
+            chain_left: 0.0,
+            since_hit: 0.0,
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        self.points
+    }
+
+    /// The factor by which points are currently multiplied.
+    pub fn multiplier(&self) -> u32 {
+        ::std::cmp::min(1 + self.chain / KILLS_PER_MULTIPLIER, MAX_MULTIPLIER)
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.chain_left -= dt;
+        if self.chain_left <= 0.0 {
+            self.chain = 0;
+            self.chain_left = 0.0;
+        }
+
+        self.since_hit += dt;
+        if self.since_hit >= NO_HIT_INTERVAL {
+            self.since_hit -= NO_HIT_INTERVAL;
+            self.points += NO_HIT_BONUS;
+        }
+    }
+
+    /// Awards the points for an asteroid of the given size and speed, and
+    /// extends the current chain. Smaller and faster asteroids are worth more.
+    pub fn asteroid_destroyed(&mut self, side: f64, vel: f64) {
+        let base = ASTEROID_POINTS * (REFERENCE_SIDE / side.max(MIN_SIDE)) * (vel / REFERENCE_SPEED);
+        let points = (base.round().max(1.0) as u32).saturating_mul(self.multiplier());
+        self.points = self.points.saturating_add(points);
+        self.chain += 1;
+        self.chain_left = CHAIN_WINDOW;
+    }
+
+    /// Awards the points for an enemy ship, and extends the current chain.
+    pub fn enemy_destroyed(&mut self) {
+        self.points += ENEMY_POINTS * self.multiplier();
+        self.chain += 1;
+        self.chain_left = CHAIN_WINDOW;
+    }
+
+    /// Awards the points for a boss. They are not multiplied, as they would
+    /// otherwise dwarf everything else.
+    pub fn boss_destroyed(&mut self) {
+        self.points += BOSS_POINTS;
+    }
+
+    /// Breaks the current chain and restarts the no-hit bonus countdown.
+    pub fn player_hit(&mut self) {
+        self.chain = 0;
+        self.chain_left = 0.0;
+        self.since_hit = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smaller_and_faster_asteroids_are_worth_more() {
+        let points = |side, vel| {
+            let mut score = Score::new();
+            score.asteroid_destroyed(side, vel);
+            score.points()
+        };
+        assert_eq!(points(REFERENCE_SIDE, REFERENCE_SPEED), 100);
+        assert_eq!(points(REFERENCE_SIDE / 2.0, REFERENCE_SPEED), 200);
+        assert_eq!(points(REFERENCE_SIDE, REFERENCE_SPEED * 3.0), 300);
+        // Every asteroid is worth something
+        assert_eq!(points(REFERENCE_SIDE, 0.0), 1);
+        // Tiny asteroids are capped
+        assert_eq!(points(1.0, REFERENCE_SPEED), points(MIN_SIDE, REFERENCE_SPEED));
+        assert_eq!(points(0.0, REFERENCE_SPEED), 1200);
+        // Absurd values saturate instead of overflowing
+        assert_eq!(points(1.0, 1e30), u32::MAX);
+    }
+
+    #[test]
+    fn chains_increase_the_multiplier() {
+        let mut score = Score::new();
+        assert_eq!(score.multiplier(), 1);
+        for _ in 0..KILLS_PER_MULTIPLIER {
+            score.asteroid_destroyed(REFERENCE_SIDE, REFERENCE_SPEED);
+        }
+        assert_eq!(score.points(), 300);
+        assert_eq!(score.multiplier(), 2);
+        score.enemy_destroyed();
+        assert_eq!(score.points(), 300 + 2 * ENEMY_POINTS);
+        // Bosses are never multiplied
+        score.boss_destroyed();
+        assert_eq!(score.points(), 300 + 2 * ENEMY_POINTS + BOSS_POINTS);
+
+        for _ in 0..100 {
+            score.enemy_destroyed();
+        }
+        assert_eq!(score.multiplier(), MAX_MULTIPLIER);
+    }
+
+    #[test]
+    fn chains_break_when_they_run_out_of_time_or_the_player_is_hit() {
+        let mut score = Score::new();
+        for _ in 0..KILLS_PER_MULTIPLIER {
+            score.enemy_destroyed();
+        }
+        score.update(CHAIN_WINDOW - 0.1);
+        assert_eq!(score.multiplier(), 2);
+        score.update(0.2);
+        assert_eq!(score.multiplier(), 1);
+
+        for _ in 0..KILLS_PER_MULTIPLIER {
+            score.enemy_destroyed();
+        }
+        score.player_hit();
+        assert_eq!(score.multiplier(), 1);
+    }
+
+    #[test]
+    fn surviving_without_getting_hit_earns_a_bonus() {
+        let mut score = Score::new();
+        score.update(NO_HIT_INTERVAL - 1.0);
+        score.player_hit();
+        score.update(NO_HIT_INTERVAL - 1.0);
+        assert_eq!(score.points(), 0);
+        score.update(1.0);
+        assert_eq!(score.points(), NO_HIT_BONUS);
+        // Small steps add up to the same bonus, give or take a rounding error
+        for _ in 0..(NO_HIT_INTERVAL * 60.0) as u32 + 1 {
+            score.update(1.0 / 60.0);
+        }
+        assert_eq!(score.points(), 2 * NO_HIT_BONUS);
+    }
+}
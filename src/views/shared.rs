@@ -1,6 +1,8 @@
+use phi::FIXED_DT;
 use phi::data::Rectangle;
 use phi::gfx::{CopySprite, Sprite};
 use sdl2::render::Renderer;
+use views::level::Backgrounds;
 
 #[derive(Clone)]
 pub struct Background {
@@ -10,7 +12,7 @@ pub struct Background {
     pub sprite: Sprite,
 }
 impl Background {
-    pub fn render(&mut self, renderer: &mut Renderer, elapsed: f64) {
+    pub fn update(&mut self, elapsed: f64) {
         // We define a logical position as depending solely on the time and the
         // dimensions of the image, not on the screen's size.
         let size = self.sprite.size();
@@ -18,12 +20,26 @@ impl Background {
         if self.pos > size.0 {
             self.pos -= size.0
         }
+    }
+
+    /// Replaces the image of the background, and the speed at which it
+    /// scrolls, by `layer`.
+    fn load(&mut self, renderer: &mut Renderer, layer: &(String, f64)) -> Result<(), String> {
+        self.sprite = Sprite::load(renderer, &layer.0).ok_or(format!("could not load {}", layer.0))?;
+        self.vel = layer.1;
+        Ok(())
+    }
+
+    /// Renders the background, `alpha` steps ahead of its current position.
+    pub fn render(&self, renderer: &mut Renderer, alpha: f64) {
+        let size = self.sprite.size();
+        let pos = (self.pos + self.vel * alpha * FIXED_DT) % size.0;
         // We determine the scale ratio of the window to the sprite.
         let (win_w, win_h) = renderer.output_size().unwrap();
         let scale = win_h as f64 / size.1;
         // We render as many copies of the background as necessary to fill
         // the screen.
-        let mut physical_left = -self.pos * scale;
+        let mut physical_left = -pos * scale;
         while physical_left < win_w as f64 {
             renderer.copy_sprite(&self.sprite,
                                  Rectangle {
@@ -46,6 +62,20 @@ pub struct BgSet {
     pub front: Background,
 }
 impl BgSet {
+    pub fn update(&mut self, elapsed: f64) {
+        self.back.update(elapsed);
+        self.middle.update(elapsed);
+        self.front.update(elapsed);
+    }
+
+    /// Switches to the images of `backgrounds`. Layers keep their position,
+    /// so that the switch goes unnoticed if the images are the same.
+    pub fn load(&mut self, renderer: &mut Renderer, backgrounds: &Backgrounds) -> Result<(), String> {
+        self.back.load(renderer, &backgrounds.back)?;
+        self.middle.load(renderer, &backgrounds.middle)?;
+        self.front.load(renderer, &backgrounds.front)
+    }
+
     pub fn new(renderer: &mut Renderer) -> BgSet {
         BgSet {
             back: Background {
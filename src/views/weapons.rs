@@ -0,0 +1,184 @@
+use views::bullets::{BULLET_H, Bullet, CannonType, ChargeBullet, RectBullet, spawn_bullets};
+
+/// The highest power level a weapon can be upgraded to.
+const MAX_POWER: u32 = 3;
+/// Heat lost every second.
+const COOL_RATE: f64 = 0.4;
+/// Once overheated, a weapon may only fire again when its heat is back under
+/// this value.
+const RECOVERED_HEAT: f64 = 0.25;
+/// Heat added by a charge shot.
+const CHARGE_HEAT: f64 = 0.3;
+/// A charge shot is faster than regular bullets, by this factor.
+const CHARGE_SPEED: f64 = 1.5;
+
+/// How a kind of cannon behaves when fired.
+struct Stats {
+    /// Seconds between two volleys.
+    interval: f64,
+    /// Heat added by a volley. The weapon overheats at 1.
+    heat: f64,
+}
+
+fn stats(cannon: CannonType) -> Stats {
+    match cannon {
+        CannonType::RectBullet => Stats { interval: 0.15, heat: 0.06 },
+        CannonType::SineBullet { .. } => Stats { interval: 0.2, heat: 0.08 },
+        CannonType::DivergentBullet { .. } => Stats { interval: 0.3, heat: 0.12 },
+    }
+}
+
+/// The player's cannons: which kind of bullets they fire, how fast, and how
+/// hot they are.
+pub struct Weapon {
+    cannon: CannonType,
+    /// From 1 to `MAX_POWER`. Every level adds bullets to each volley.
+    power: u32,
+    /// Seconds until the weapon may fire again.
+    cooldown: f64,
+    /// From 0 to 1.
+    heat: f64,
+    /// Whether the weapon got too hot, and must cool down before firing.
+    overheated: bool,
+    /// Seconds the trigger must be held for a charge shot, or 0 if the
+    /// weapon has none.
+    charge_time: f64,
+    /// Seconds the trigger has been held, up to `charge_time`.
+    charge: f64,
+}
+
+impl Weapon {
+    pub fn new(charge_time: f64) -> Weapon {
+        Weapon {
+            cannon: CannonType::RectBullet,
+            power: 1,
+            cooldown: 0.0,
+            heat: 0.0,
+            overheated: false,
+            charge_time: charge_time,
+            charge: 0.0,
+        }
+    }
+
+    /// Switches to another kind of cannon. The heat is kept, so that
+    /// switching is no way around overheating.
+    pub fn select(&mut self, cannon: CannonType) {
+        self.cannon = cannon;
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self.cannon {
+            CannonType::RectBullet => "Blaster",
+            CannonType::SineBullet { .. } => "Wave",
+            CannonType::DivergentBullet { .. } => "Spread",
+        }
+    }
+
+    pub fn power(&self) -> u32 {
+        self.power
+    }
+
+    /// Raises the power level, up to `MAX_POWER`. Returns whether it changed.
+    pub fn power_up(&mut self) -> bool {
+        let upgraded = self.power < MAX_POWER;
+        if upgraded {
+            self.power += 1;
+        }
+        upgraded
+    }
+
+    /// Drops back to the first power level and loses the charge, e.g. when
+    /// the ship is destroyed.
+    pub fn reset(&mut self) {
+        self.power = 1;
+        self.charge = 0.0;
+    }
+
+    /// From 0 to 1.
+    pub fn heat(&self) -> f64 {
+        self.heat
+    }
+
+    pub fn is_overheated(&self) -> bool {
+        self.overheated
+    }
+
+    /// Cools the weapon down.
+    pub fn update(&mut self, dt: f64) {
+        self.cooldown = (self.cooldown - dt).max(0.0);
+        self.heat = (self.heat - COOL_RATE * dt).max(0.0);
+        if self.overheated && self.heat <= RECOVERED_HEAT {
+            self.overheated = false;
+        }
+    }
+
+    /// How far the charge shot is from being ready, from 0 to 1.
+    pub fn charge(&self) -> f64 {
+        if self.charge_time > 0.0 {
+            self.charge / self.charge_time
+        } else {
+            0.0
+        }
+    }
+
+    /// Charges the weapon while the trigger is held, `dt` seconds at a time.
+    pub fn hold(&mut self, dt: f64) {
+        self.charge = (self.charge + dt).min(self.charge_time);
+    }
+
+    /// Lets go of the trigger, firing a charge shot from `nose` if the
+    /// weapon was fully charged and is not overheated.
+    pub fn release(&mut self, speed: f64, nose: (f64, f64)) -> Vec<Box<dyn Bullet>> {
+        let charged = self.charge_time > 0.0 && self.charge >= self.charge_time;
+        self.charge = 0.0;
+        if !charged || self.overheated {
+            return vec![];
+        }
+        self.heat = (self.heat + CHARGE_HEAT).min(1.0);
+        if self.heat >= 1.0 {
+            self.overheated = true;
+        }
+        vec![Box::new(ChargeBullet::new(nose, speed * CHARGE_SPEED))]
+    }
+
+    pub fn can_fire(&self) -> bool {
+        self.cooldown <= 0.0 && !self.overheated
+    }
+
+    /// Fires a volley from the cannons of a ship, if the weapon is ready.
+    /// `speed` is the number of pixels traveled horizontally by the bullets
+    /// every second, and `nose` is where the ship's center cannon stands.
+    pub fn fire(&mut self,
+                speed: f64,
+                cannons_x: f64,
+                cannon1_y: f64,
+                cannon2_y: f64,
+                nose: (f64, f64))
+                -> Vec<Box<dyn Bullet>> {
+        if !self.can_fire() {
+            return vec![];
+        }
+        let stats = stats(self.cannon);
+        self.cooldown = stats.interval;
+        self.heat = (self.heat + stats.heat).min(1.0);
+        if self.heat >= 1.0 {
+            self.overheated = true;
+        }
+
+        let mut bullets = spawn_bullets(self.cannon, speed, cannons_x, cannon1_y, cannon2_y);
+        if self.power >= 2 {
+            // A straight shot from the nose
+            bullets.push(Box::new(RectBullet::new(nose.0, nose.1 - BULLET_H / 2.0, speed)));
+        }
+        if self.power >= 3 {
+            // A second pair of bullets, further apart
+            let spread = (cannon2_y - cannon1_y) / 3.0;
+            bullets.append(&mut spawn_bullets(self.cannon,
+                                              speed,
+                                              cannons_x - 10.0,
+                                              cannon1_y - spread,
+                                              cannon2_y + spread));
+        }
+        bullets
+    }
+}
@@ -0,0 +1,174 @@
+//! Runs whole views without a display, through the same main loop as the
+//! game, on a simulated clock. The player's input is fed from recordings.
+
+extern crate arcaders;
+
+use arcaders::phi::{self, Bindings, Phi, View, ViewAction};
+use arcaders::phi::replay::{Recorder, StepInput};
+use arcaders::views::game::GameView;
+use arcaders::views::main_menu::MainMenuView;
+use arcaders::views::shared::BgSet;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Mutex, MutexGuard};
+
+/// SDL may only be initialized once at a time, while tests run in parallel.
+fn lock_sdl() -> MutexGuard<'static, ()> {
+    static SDL_LOCK: Mutex<()> = Mutex::new(());
+    let guard = SDL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    // Keep the high scores, controls and demo of the user out of the tests
+    let data = ::std::env::temp_dir().join(format!("arcaders-data-{}", ::std::process::id()));
+    ::std::env::set_var("XDG_DATA_HOME", data);
+    guard
+}
+
+/// Writes a recording in which each of `steps` holds the given logical keys
+/// for the given number of steps, and returns its path.
+fn recording(name: &str, steps: &[(u32, &[&str])]) -> PathBuf {
+    let path = ::std::env::temp_dir()
+        .join(format!("arcaders-{}-{}.rec", name, ::std::process::id()));
+    let names = Bindings::names();
+    let mut recorder = Recorder::create(&path, 1, &StepInput::default()).unwrap();
+    for &(count, keys) in steps {
+        let mut input = StepInput::default();
+        for key in keys {
+            input.keys |= 1 << names.iter().position(|name| name == key).unwrap();
+        }
+        for _ in 0..count {
+            recorder.step(&input).unwrap();
+        }
+    }
+    recorder.finish().unwrap();
+    path
+}
+
+/// What a `Probe` saw.
+#[derive(Default)]
+struct Log {
+    updates: u32,
+    actions: Vec<&'static str>,
+}
+
+/// Stands between the main loop and a view, to record what it asks for.
+/// Replacements are carried out by the probe itself, so that it keeps
+/// watching the new view.
+struct Probe {
+    inner: Box<dyn View>,
+    log: Rc<RefCell<Log>>,
+}
+
+impl View for Probe {
+    fn enter(&mut self, phi: &mut Phi) {
+        self.inner.enter(phi);
+    }
+
+    fn exit(&mut self, phi: &mut Phi) {
+        self.inner.exit(phi);
+    }
+
+    fn update(&mut self, phi: &mut Phi, elapsed: f64) -> ViewAction {
+        let mut log = self.log.borrow_mut();
+        log.updates += 1;
+        match self.inner.update(phi, elapsed) {
+            ViewAction::None => ViewAction::None,
+            ViewAction::Replace(view) => {
+                log.actions.push("replace");
+                self.inner.exit(phi);
+                self.inner = view;
+                self.inner.enter(phi);
+                ViewAction::None
+            }
+            action => {
+                log.actions.push(match action {
+                    ViewAction::Quit => "quit",
+                    ViewAction::Push(_) => "push",
+                    ViewAction::Pop => "pop",
+                    _ => "reset",
+                });
+                action
+            }
+        }
+    }
+
+    fn draw(&self, phi: &mut Phi, alpha: f64) {
+        self.inner.draw(phi, alpha);
+    }
+}
+
+/// Runs the view made by `init` for `frames` frames, playing back the
+/// recording at `input` if any, and returns what it did.
+fn run<F>(frames: u32, input: Option<PathBuf>, init: F) -> Log
+    where F: Fn(&mut Phi) -> Box<dyn View>
+{
+    let log = Rc::new(RefCell::new(Log::default()));
+    phi::spawn_headless(800, 600, frames, |phi| {
+        phi.reseed(1);
+        if let Some(ref path) = input {
+            phi.start_replay(path).unwrap();
+        }
+        Box::new(Probe {
+            inner: init(phi),
+            log: log.clone(),
+        })
+    });
+    if let Some(path) = input {
+        ::std::fs::remove_file(path).unwrap();
+    }
+    Rc::try_unwrap(log).ok().unwrap().into_inner()
+}
+
+#[test]
+fn the_main_menu_waits_for_the_player() {
+    let _lock = lock_sdl();
+    let log = run(120, None, |phi| Box::new(MainMenuView::new(phi)));
+    assert!(log.updates >= 120);
+    assert!(log.actions.is_empty());
+}
+
+#[test]
+fn the_main_menu_starts_a_game() {
+    let _lock = lock_sdl();
+    let input = recording("new-game", &[(10, &[]), (1, &["key_enter"]), (300, &["key_space"])]);
+    let log = run(320, Some(input), |phi| Box::new(MainMenuView::new(phi)));
+    assert_eq!(log.actions, vec!["replace"]);
+}
+
+#[test]
+fn the_main_menu_quits() {
+    let _lock = lock_sdl();
+    let input = recording("quit",
+                          &[(1, &["key_down"]), (1, &[]), (1, &["key_down"]), (1, &[]),
+                            (1, &["key_enter"])]);
+    let log = run(600, Some(input), |phi| Box::new(MainMenuView::new(phi)));
+    assert_eq!(log.actions, vec!["quit"]);
+    assert_eq!(log.updates, 5);
+}
+
+#[test]
+fn a_game_plays_on_its_own() {
+    let _lock = lock_sdl();
+    let log = run(600, None, |phi| {
+        let bg = BgSet::new(&mut phi.renderer);
+        Box::new(GameView::with_backgrounds(phi, bg))
+    });
+    assert!(log.updates >= 600);
+    assert!(!log.actions.contains(&"quit"));
+}
+
+#[test]
+fn a_game_responds_to_the_player() {
+    let _lock = lock_sdl();
+    // Fly around while firing and charging a shot, release it, then pause
+    // the game
+    let input = recording("game",
+                          &[(120, &["key_space", "key_up"]),
+                            (120, &["key_space", "key_right", "key_down"]),
+                            (30, &["key_left"]),
+                            (1, &["key_escape"])]);
+    let log = run(600, Some(input), |phi| {
+        let bg = BgSet::new(&mut phi.renderer);
+        Box::new(GameView::with_backgrounds(phi, bg))
+    });
+    assert_eq!(log.actions.last(), Some(&"push"));
+}